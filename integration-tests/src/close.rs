@@ -0,0 +1,166 @@
+use nvim_oxi::api::types::{WindowConfig, WindowRelativeTo};
+use nvim_oxi::api::{Buffer, Window};
+use nvim_oxi::conversion::FromObject;
+use nvim_oxi::{Array, Dictionary};
+
+/// Splits off a window showing a new unnamed buffer, keeping the current window focused
+fn split_with_unnamed_buf() -> (Window, Buffer) {
+    let buf = nvim_oxi::api::create_buf(true, false).unwrap();
+    let split =
+        nvim_oxi::api::open_win(&buf, false, &WindowConfig::builder().vertical(true).build())
+            .unwrap();
+    (split, buf)
+}
+
+fn open_float() -> Window {
+    let buf = nvim_oxi::api::create_buf(false, true).unwrap();
+    let cfg = WindowConfig::builder()
+        .relative(WindowRelativeTo::Editor)
+        .width(10)
+        .height(3)
+        .row(1.0)
+        .col(1.0)
+        .build();
+    nvim_oxi::api::open_win(&buf, false, &cfg).unwrap()
+}
+
+fn close_opts(include_current_win: bool) -> Dictionary {
+    let mut filter_rules = Dictionary::new();
+    filter_rules.insert("include_current_win", include_current_win);
+    let mut opts = Dictionary::new();
+    opts.insert("filter_rules", filter_rules);
+    let mut dict = Dictionary::new();
+    dict.insert("opts", opts);
+    dict
+}
+
+fn summary_windows(summary: &Dictionary, key: &str) -> Vec<i32> {
+    let list = Array::from_object(summary.get(key).unwrap().clone()).unwrap();
+    list.into_iter()
+        .map(|obj| {
+            i32::from_object(obj.clone()).unwrap_or_else(|_| {
+                let skipped = Dictionary::from_object(obj).unwrap();
+                i32::from_object(skipped.get("win").unwrap().clone()).unwrap()
+            })
+        })
+        .collect()
+}
+
+fn skip_reasons(summary: &Dictionary) -> Vec<String> {
+    Array::from_object(summary.get("skipped").unwrap().clone())
+        .unwrap()
+        .into_iter()
+        .map(|obj| {
+            let skipped = Dictionary::from_object(obj).unwrap();
+            String::from_object(skipped.get("reason").unwrap().clone()).unwrap()
+        })
+        .collect()
+}
+
+#[nvim_oxi::test]
+fn discard_resets_unnamed_buffer() {
+    let (split, mut buf) = split_with_unnamed_buf();
+    buf.set_lines(.., false, ["changed"]).unwrap();
+    let mut dict = close_opts(false);
+    dict.insert("modified", "discard");
+    let summary = nvim_winpick_core::pick_close_window(Some(dict.into()));
+    assert_eq!(vec![split.handle()], summary_windows(&summary, "closed"));
+    assert!(buf.is_valid());
+    #[allow(deprecated)]
+    let modified: bool = buf.get_option("modified").unwrap();
+    assert!(!modified);
+    let lines: Vec<String> = buf
+        .get_lines(.., false)
+        .unwrap()
+        .map(|line| line.to_string_lossy().into_owned())
+        .collect();
+    assert_eq!(vec![String::new()], lines);
+}
+
+#[nvim_oxi::test]
+fn last_window_of_tab_is_skipped() {
+    nvim_oxi::api::command("tabnew").unwrap();
+    let win = nvim_oxi::api::get_current_win();
+    let summary = nvim_winpick_core::pick_close_window(Some(close_opts(true).into()));
+    assert!(win.is_valid());
+    assert_eq!(vec![win.handle()], summary_windows(&summary, "skipped"));
+    assert_eq!(vec!["last window".to_string()], skip_reasons(&summary));
+}
+
+#[nvim_oxi::test]
+fn last_split_next_to_floats_is_skipped() {
+    let win = nvim_oxi::api::get_current_win();
+    let float = open_float();
+    // Floats aren't candidates by default, leaving only the current window
+    let summary = nvim_winpick_core::pick_close_window(Some(close_opts(true).into()));
+    assert!(win.is_valid());
+    assert!(float.is_valid());
+    assert_eq!(
+        vec!["only floating windows would remain".to_string()],
+        skip_reasons(&summary)
+    );
+}
+
+/// Three side by side windows with the first one focused, in tab order
+fn three_windows() -> Vec<Window> {
+    let original = nvim_oxi::api::get_current_win();
+    nvim_oxi::api::command("vsplit | vsplit").unwrap();
+    nvim_oxi::api::set_current_win(&original).unwrap();
+    nvim_oxi::api::get_current_tabpage()
+        .list_wins()
+        .unwrap()
+        .collect()
+}
+
+fn only_opts() -> Dictionary {
+    let mut opts = Dictionary::new();
+    opts.insert("hint", "floating-letter");
+    let mut dict = Dictionary::new();
+    dict.insert("opts", opts);
+    dict
+}
+
+fn type_keys(keys: &str) {
+    nvim_oxi::api::call_function::<_, i64>("feedkeys", (keys, "n")).unwrap();
+}
+
+#[nvim_oxi::test]
+fn only_keeps_picked_window() {
+    let windows = three_windows();
+    // Labels follow the tab order, the second default selection char picks the second window
+    type_keys("j");
+    let summary = nvim_winpick_core::pick_only_windows(Some(only_opts().into()));
+    let tab_windows: Vec<Window> = nvim_oxi::api::get_current_tabpage()
+        .list_wins()
+        .unwrap()
+        .collect();
+    assert_eq!(vec![windows[1].clone()], tab_windows);
+    assert_eq!(windows[1], nvim_oxi::api::get_current_win());
+    let mut closed = summary_windows(&summary, "closed");
+    closed.sort_unstable();
+    let mut expected = vec![windows[0].handle(), windows[2].handle()];
+    expected.sort_unstable();
+    assert_eq!(expected, closed);
+}
+
+#[nvim_oxi::test]
+fn only_leaves_floats_open() {
+    let win = nvim_oxi::api::get_current_win();
+    let float = open_float();
+    // Floats aren't candidates by default, the current window is kept without prompting
+    let summary = nvim_winpick_core::pick_only_windows(Some(only_opts().into()));
+    assert!(win.is_valid());
+    assert!(float.is_valid());
+    assert!(summary_windows(&summary, "closed").is_empty());
+}
+
+#[nvim_oxi::test]
+fn cancelled_only_closes_nothing() {
+    let windows = three_windows();
+    // Not a selection char
+    type_keys("z");
+    let summary = nvim_winpick_core::pick_only_windows(Some(only_opts().into()));
+    assert!(windows.iter().all(Window::is_valid));
+    assert!(summary_windows(&summary, "closed").is_empty());
+    assert!(summary_windows(&summary, "skipped").is_empty());
+}
@@ -0,0 +1,71 @@
+use nvim_oxi::Dictionary;
+use nvim_oxi::api::Window;
+
+fn row(win: &Window) -> usize {
+    win.get_position().unwrap().0
+}
+
+fn col(win: &Window) -> usize {
+    win.get_position().unwrap().1
+}
+
+fn setup() {
+    let mut opts = Dictionary::new();
+    opts.insert("hint", "floating-letter");
+    nvim_winpick_core::setup(Some(opts.into()));
+}
+
+#[nvim_oxi::test]
+fn modifiers_pick_side_of_split() {
+    setup();
+    let original = nvim_oxi::api::get_current_win();
+    // The only window is autoselected
+    nvim_oxi::api::command("aboveleft Winpick split winpick_above").unwrap();
+    let new = nvim_oxi::api::get_current_win();
+    assert_ne!(original, new);
+    assert!(row(&new) < row(&original));
+    // Labels follow the tab order, the first default selection char picks the top window
+    nvim_oxi::api::call_function::<_, i64>("feedkeys", ("f", "n")).unwrap();
+    nvim_oxi::api::command("vertical belowright Winpick split winpick_right").unwrap();
+    let right = nvim_oxi::api::get_current_win();
+    assert!(col(&right) > col(&new));
+    assert_eq!(row(&right), row(&new));
+}
+
+#[nvim_oxi::test]
+fn modifiers_are_rejected_outside_split() {
+    setup();
+    let before = nvim_oxi::api::list_wins().count();
+    nvim_oxi::api::command("vertical Winpick over winpick_over").unwrap();
+    nvim_oxi::api::command("aboveleft Winpick relative winpick_relative").unwrap();
+    assert_eq!(before, nvim_oxi::api::list_wins().count());
+    let listed: Vec<String> = nvim_oxi::api::list_bufs()
+        .map(|buf| buf.get_name().unwrap().display().to_string())
+        .collect();
+    assert!(!listed.iter().any(|name| name.ends_with("winpick_over")));
+}
+
+#[nvim_oxi::test]
+fn tab_modifier_is_rejected() {
+    setup();
+    let before = nvim_oxi::api::list_wins().count();
+    nvim_oxi::api::command("tab Winpick split winpick_tab").unwrap();
+    nvim_oxi::api::command("hide Winpick close").unwrap();
+    assert_eq!(1, nvim_oxi::api::list_tabpages().count());
+    assert_eq!(before, nvim_oxi::api::list_wins().count());
+    let listed: Vec<String> = nvim_oxi::api::list_bufs()
+        .map(|buf| buf.get_name().unwrap().display().to_string())
+        .collect();
+    assert!(!listed.iter().any(|name| name.ends_with("winpick_tab")));
+}
+
+#[nvim_oxi::test]
+fn message_modifiers_are_kept() {
+    setup();
+    nvim_oxi::api::command("vsplit").unwrap();
+    let left = nvim_oxi::api::get_current_win();
+    // The second default selection char picks the right window
+    nvim_oxi::api::call_function::<_, i64>("feedkeys", ("j", "n")).unwrap();
+    nvim_oxi::api::command("silent Winpick focus").unwrap();
+    assert_ne!(left, nvim_oxi::api::get_current_win());
+}
@@ -0,0 +1,119 @@
+use nvim_oxi::api::Window;
+use nvim_oxi::api::opts::SetKeymapOpts;
+use nvim_oxi::api::types::Mode;
+use nvim_oxi::{Dictionary, Object};
+
+/// Three side by side windows with the first one focused, in tab order, which is label order
+fn three_windows() -> Vec<Window> {
+    let original = nvim_oxi::api::get_current_win();
+    nvim_oxi::api::command("vsplit | vsplit").unwrap();
+    nvim_oxi::api::set_current_win(&original).unwrap();
+    nvim_oxi::api::get_current_tabpage()
+        .list_wins()
+        .unwrap()
+        .collect()
+}
+
+/// `:normal` runs the keys right away, a pick that draws is cancelled once they run out
+fn normal(keys: &str) {
+    nvim_oxi::api::command(&format!(r#"execute "normal {keys}""#)).unwrap();
+}
+
+#[nvim_oxi::test]
+fn counted_plug_mapping_picks_by_label_position() {
+    nvim_winpick_core::setup(None);
+    let windows = three_windows();
+    normal(r"2\<Plug>(winpick-focus)");
+    assert_eq!(windows[1], nvim_oxi::api::get_current_win());
+}
+
+#[nvim_oxi::test]
+fn uncounted_plug_mapping_draws() {
+    nvim_winpick_core::setup(None);
+    let windows = three_windows();
+    normal(r"\<Plug>(winpick-focus)");
+    assert_eq!(windows[0], nvim_oxi::api::get_current_win());
+}
+
+#[nvim_oxi::test]
+fn count_ignored_outside_plug_mappings() {
+    let windows = three_windows();
+    // `v:count` is 2 in here, but it's not a `<Plug>` mapping
+    let opts = SetKeymapOpts::builder()
+        .callback(|()| nvim_winpick_core::pick_focus_window(None))
+        .build();
+    nvim_oxi::api::set_keymap(Mode::Normal, "<F2>", "", &opts).unwrap();
+    normal(r"2\<F2>");
+    assert_eq!(windows[0], nvim_oxi::api::get_current_win());
+}
+
+/// Opts with only `count` set
+fn counted(count: i64) -> Object {
+    let mut opts = Dictionary::new();
+    opts.insert("count", count);
+    opts.into()
+}
+
+#[nvim_oxi::test]
+fn count_opt_picks_by_label_position() {
+    let windows = three_windows();
+    let picked = nvim_winpick_core::pick_window(Some(counted(2)));
+    assert_eq!(Some(windows[1].clone()), picked);
+    assert_eq!(windows[0], nvim_oxi::api::get_current_win());
+}
+
+#[nvim_oxi::test]
+fn count_opt_resolves_the_first_pick_of_an_action() {
+    let windows = three_windows();
+    nvim_winpick_core::pick_focus_window(Some(counted(3)));
+    assert_eq!(windows[2], nvim_oxi::api::get_current_win());
+}
+
+#[nvim_oxi::test]
+fn count_opt_nested_in_action_opts() {
+    let windows = three_windows();
+    let mut opts = Dictionary::new();
+    opts.insert("opts", counted(2));
+    let summary = nvim_winpick_core::pick_close_window(Some(opts.into()));
+    assert!(summary.get("closed").is_some());
+    assert!(!windows[1].is_valid());
+    assert!(windows[0].is_valid() && windows[2].is_valid());
+}
+
+#[nvim_oxi::test]
+fn count_opt_beyond_the_labels_picks_nothing() {
+    three_windows();
+    assert_eq!(None, nvim_winpick_core::pick_window(Some(counted(4))));
+}
+
+#[nvim_oxi::test]
+fn count_opt_passes_v_count_from_a_mapping() {
+    nvim_winpick_core::setup(None);
+    let windows = three_windows();
+    let opts = SetKeymapOpts::builder()
+        .callback(|()| {
+            let count = nvim_oxi::api::get_vvar("count").unwrap();
+            nvim_winpick_core::pick_focus_window(Some(counted(count)));
+        })
+        .build();
+    nvim_oxi::api::set_keymap(Mode::Normal, "<F2>", "", &opts).unwrap();
+    normal(r"3\<F2>");
+    assert_eq!(windows[2], nvim_oxi::api::get_current_win());
+}
+
+#[nvim_oxi::test]
+fn setup_rejects_a_count() {
+    nvim_winpick_core::setup(None);
+    let mut profile = Dictionary::new();
+    profile.insert("count", 2);
+    let mut profiles = Dictionary::new();
+    profiles.insert("second", profile);
+    let mut dict = Dictionary::new();
+    dict.insert("selection_chars", "abcdefg");
+    dict.insert("profiles", profiles);
+    nvim_winpick_core::setup(Some(dict.into()));
+    assert_ne!(
+        "abcdefg",
+        nvim_winpick_core::Opts::default().selection_chars
+    );
+}
@@ -0,0 +1,109 @@
+use nvim_oxi::Dictionary;
+use nvim_oxi::api::{Buffer, Window};
+
+/// Splits off a window showing a new scratch buffer, returns the original window and the new
+/// one with the original focused
+fn two_windows() -> (Window, Window) {
+    let original = nvim_oxi::api::get_current_win();
+    nvim_oxi::api::command("vsplit").unwrap();
+    let mut other = nvim_oxi::api::get_current_win();
+    let buf = nvim_oxi::api::create_buf(true, true).unwrap();
+    other.set_buf(&buf).unwrap();
+    nvim_oxi::api::set_current_win(&original).unwrap();
+    (original, other)
+}
+
+/// Picks the only other window without prompting
+fn other_window_opts() -> Dictionary {
+    let mut filter_rules = Dictionary::new();
+    filter_rules.insert("include_current_win", false);
+    let mut opts = Dictionary::new();
+    opts.insert("filter_rules", filter_rules);
+    opts
+}
+
+fn buffers_in_tab() -> Vec<Buffer> {
+    nvim_oxi::api::get_current_tabpage()
+        .list_wins()
+        .unwrap()
+        .map(|win| win.get_buf().unwrap())
+        .collect()
+}
+
+fn buf(win: &Window) -> Buffer {
+    win.get_buf().unwrap()
+}
+
+#[nvim_oxi::test]
+fn undo_and_redo_swap() {
+    let (original, other) = two_windows();
+    let (original_buf, other_buf) = (buf(&original), buf(&other));
+    nvim_winpick_core::pick_swap_window(Some(other_window_opts().into()));
+    assert_eq!(other_buf, buf(&original));
+    nvim_winpick_core::winpick_undo();
+    assert_eq!(original_buf, buf(&original));
+    assert_eq!(other_buf, buf(&other));
+    nvim_winpick_core::winpick_redo();
+    assert_eq!(other_buf, buf(&original));
+    assert_eq!(original_buf, buf(&other));
+}
+
+#[nvim_oxi::test]
+fn undo_and_redo_close() {
+    let (_, other) = two_windows();
+    let other_buf = buf(&other);
+    let mut dict = Dictionary::new();
+    dict.insert("opts", other_window_opts());
+    let _ = nvim_winpick_core::pick_close_window(Some(dict.into()));
+    assert!(!other.is_valid());
+    nvim_winpick_core::winpick_undo();
+    // Reopened, as a new window showing the same buffer
+    assert_eq!(2, buffers_in_tab().len());
+    assert!(buffers_in_tab().contains(&other_buf));
+    nvim_winpick_core::winpick_redo();
+    assert_eq!(1, buffers_in_tab().len());
+    assert!(!buffers_in_tab().contains(&other_buf));
+}
+
+#[nvim_oxi::test]
+fn undo_and_redo_open_split() {
+    let (original, _) = two_windows();
+    let opened = nvim_oxi::api::create_buf(true, true).unwrap();
+    let mut dict = Dictionary::new();
+    dict.insert("buf", opened.handle());
+    dict.insert("opts", other_window_opts());
+    nvim_winpick_core::open_split(Some(dict.into()));
+    assert_eq!(3, buffers_in_tab().len());
+    nvim_winpick_core::winpick_undo();
+    assert_eq!(2, buffers_in_tab().len());
+    assert!(!buffers_in_tab().contains(&opened));
+    // Focus is part of what's restored
+    assert_eq!(original, nvim_oxi::api::get_current_win());
+    nvim_winpick_core::winpick_redo();
+    assert_eq!(3, buffers_in_tab().len());
+    assert!(buffers_in_tab().contains(&opened));
+}
+
+#[nvim_oxi::test]
+fn undo_brings_back_window_moved_to_another_tab() {
+    let (original, other) = two_windows();
+    let tab = nvim_oxi::api::get_current_tabpage();
+    let other_buf = buf(&other);
+    let mut dict = Dictionary::new();
+    dict.insert("opts", other_window_opts());
+    // With no other tabs, the first selection char labels a new tab
+    nvim_oxi::api::call_function::<_, i64>("feedkeys", ("f", "n")).unwrap();
+    nvim_winpick_core::pick_move_to_tab(Some(dict.into()));
+    assert_eq!(2, nvim_oxi::api::list_tabpages().count());
+    // History is kept per tab, undo from the one the window was moved out of
+    nvim_oxi::api::set_current_tabpage(&tab).unwrap();
+    assert_eq!(vec![buf(&original)], buffers_in_tab());
+    nvim_winpick_core::winpick_undo();
+    // The tab it was moved to only had that window, so it's gone with it
+    assert_eq!(1, nvim_oxi::api::list_tabpages().count());
+    assert_eq!(2, buffers_in_tab().len());
+    assert!(buffers_in_tab().contains(&other_buf));
+    nvim_winpick_core::winpick_redo();
+    assert_eq!(1, buffers_in_tab().len());
+    assert!(!buffers_in_tab().contains(&other_buf));
+}
@@ -0,0 +1,178 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use nvim_oxi::api::opts::CreateAutocmdOpts;
+use nvim_oxi::api::types::AutocmdCallbackArgs;
+use nvim_oxi::conversion::FromObject;
+use nvim_oxi::{Array, Dictionary, Function, Object};
+
+type Events = Rc<RefCell<Vec<Dictionary>>>;
+
+/// A hook that keeps every event it's called with
+fn recording_hook(events: &Events) -> Object {
+    let events = Rc::clone(events);
+    Function::<Dictionary, ()>::from_fn(move |event: Dictionary| events.borrow_mut().push(event))
+        .into()
+}
+
+/// Keeps the data of every `User` autocmd matching `pattern`
+fn record_user_autocmd(pattern: &str) -> Events {
+    let events = Events::default();
+    let recorded = Rc::clone(&events);
+    let opts = CreateAutocmdOpts::builder()
+        .patterns([pattern])
+        .callback(move |args: AutocmdCallbackArgs| {
+            recorded
+                .borrow_mut()
+                .push(Dictionary::from_object(args.data).unwrap());
+            false
+        })
+        .build();
+    nvim_oxi::api::create_autocmd(["User"], &opts).unwrap();
+    events
+}
+
+fn get<T: FromObject>(event: &Dictionary, key: &str) -> Option<T> {
+    event
+        .get(key)
+        .and_then(|obj| T::from_object(obj.clone()).ok())
+}
+
+#[nvim_oxi::test]
+fn hooks_and_autocmds_follow_a_pick() {
+    let picked = Events::default();
+    let done = Events::default();
+    let cancelled = Events::default();
+    let mut hooks = Dictionary::new();
+    hooks.insert("on_pick", recording_hook(&picked));
+    hooks.insert("on_action_done", recording_hook(&done));
+    hooks.insert("on_cancel", recording_hook(&cancelled));
+    let mut opts = Dictionary::new();
+    opts.insert("hooks", hooks);
+    let pre = record_user_autocmd("WinpickPre");
+    let post = record_user_autocmd("WinpickPost");
+    // The only window is autoselected
+    nvim_winpick_core::pick_focus_window(Some(opts.into()));
+    let win = nvim_oxi::api::get_current_win().handle();
+    assert!(cancelled.borrow().is_empty());
+    for events in [&picked, &done] {
+        let events = events.borrow();
+        assert_eq!(1, events.len());
+        assert_eq!(Some("focus".to_string()), get(&events[0], "action"));
+        let picked: Array = get(&events[0], "picked").unwrap();
+        assert_eq!(
+            Some(win),
+            picked
+                .into_iter()
+                .next()
+                .and_then(|obj| i32::from_object(obj).ok())
+        );
+    }
+    assert_eq!(Some("focus".to_string()), get(&pre.borrow()[0], "action"));
+    assert_eq!(Some(false), get(&post.borrow()[0], "cancelled"));
+}
+
+#[nvim_oxi::test]
+fn cancelled_pick_skips_action_done() {
+    let done = Events::default();
+    let cancelled = Events::default();
+    let mut hooks = Dictionary::new();
+    hooks.insert("on_action_done", recording_hook(&done));
+    hooks.insert("on_cancel", recording_hook(&cancelled));
+    let mut filter_rules = Dictionary::new();
+    filter_rules.insert("include_current_win", false);
+    let mut opts = Dictionary::new();
+    opts.insert("hooks", hooks);
+    opts.insert("filter_rules", filter_rules);
+    let post = record_user_autocmd("WinpickPost");
+    // Nothing left to pick from once the only window is filtered out
+    nvim_winpick_core::pick_swap_window(Some(opts.into()));
+    assert!(done.borrow().is_empty());
+    assert_eq!(1, cancelled.borrow().len());
+    assert_eq!(
+        Some("swap".to_string()),
+        get(&cancelled.borrow()[0], "action")
+    );
+    assert_eq!(Some(true), get(&post.borrow()[0], "cancelled"));
+}
+
+#[nvim_oxi::test]
+fn setup_hooks_are_merged_under_call_site_hooks() {
+    let from_setup = Events::default();
+    let from_call = Events::default();
+    let mut hooks = Dictionary::new();
+    hooks.insert("on_pick", recording_hook(&from_setup));
+    hooks.insert("on_action_done", recording_hook(&from_setup));
+    let mut setup = Dictionary::new();
+    setup.insert("hooks", hooks);
+    nvim_winpick_core::setup(Some(setup.into()));
+    let mut hooks = Dictionary::new();
+    hooks.insert("on_action_done", recording_hook(&from_call));
+    let mut opts = Dictionary::new();
+    opts.insert("hooks", hooks);
+    nvim_winpick_core::pick_focus_window(Some(opts.into()));
+    assert_eq!(1, from_setup.borrow().len());
+    assert_eq!(1, from_call.borrow().len());
+}
+
+#[nvim_oxi::test]
+fn cancelled_position_cancels_action() {
+    let done = Events::default();
+    let cancelled = Events::default();
+    let mut hooks = Dictionary::new();
+    hooks.insert("on_action_done", recording_hook(&done));
+    hooks.insert("on_cancel", recording_hook(&cancelled));
+    let mut opts = Dictionary::new();
+    opts.insert("hooks", hooks);
+    let mut keys = Dictionary::new();
+    keys.insert("split_bottom", "x");
+    let mut dict = Dictionary::new();
+    dict.insert(
+        "buf",
+        nvim_oxi::api::create_buf(false, true).unwrap().handle(),
+    );
+    dict.insert("relative_keys", keys);
+    dict.insert("opts", opts);
+    let post = record_user_autocmd("WinpickPost");
+    let before = nvim_oxi::api::list_wins().count();
+    // The only window is autoselected, then a key that isn't a position cancels
+    nvim_oxi::api::call_function::<_, i64>("feedkeys", ("z", "n")).unwrap();
+    nvim_winpick_core::pick_win_relative(Some(dict.into()));
+    assert_eq!(before, nvim_oxi::api::list_wins().count());
+    assert!(done.borrow().is_empty());
+    assert_eq!(1, cancelled.borrow().len());
+    assert_eq!(Some(true), get(&post.borrow()[0], "cancelled"));
+}
+
+#[nvim_oxi::test]
+fn undo_and_redo_fire_setup_hooks() {
+    let done = Events::default();
+    let mut hooks = Dictionary::new();
+    hooks.insert("on_action_done", recording_hook(&done));
+    let mut setup = Dictionary::new();
+    setup.insert("hooks", hooks);
+    nvim_winpick_core::setup(Some(setup.into()));
+    let pre = record_user_autocmd("WinpickPre");
+    nvim_oxi::api::command("vsplit").unwrap();
+    let mut filter_rules = Dictionary::new();
+    filter_rules.insert("include_current_win", false);
+    let mut opts = Dictionary::new();
+    opts.insert("filter_rules", filter_rules);
+    nvim_winpick_core::pick_swap_window(Some(opts.into()));
+    nvim_winpick_core::winpick_undo();
+    nvim_winpick_core::winpick_redo();
+    let actions: Vec<Option<String>> = done
+        .borrow()
+        .iter()
+        .map(|event| get(event, "action"))
+        .collect();
+    assert_eq!(
+        vec![
+            Some("swap".to_string()),
+            Some("undo".to_string()),
+            Some("redo".to_string())
+        ],
+        actions
+    );
+    assert_eq!(3, pre.borrow().len());
+}
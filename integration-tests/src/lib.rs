@@ -1 +1,11 @@
+mod close;
+mod command;
+mod count;
+mod history;
+mod hooks;
+mod open_many;
+mod operator;
 mod opts;
+mod relative;
+mod repeat;
+mod tab;
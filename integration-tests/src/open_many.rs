@@ -0,0 +1,34 @@
+use nvim_oxi::api::opts::OptionOpts;
+use nvim_oxi::{Array, Dictionary};
+
+/// The text on the command line
+fn cmdline() -> String {
+    let lines: i64 = nvim_oxi::api::get_option_value("lines", &OptionOpts::default()).unwrap();
+    let columns: i64 = nvim_oxi::api::get_option_value("columns", &OptionOpts::default()).unwrap();
+    (1..=columns)
+        .map(|col| nvim_oxi::api::call_function::<_, String>("screenstring", (lines, col)).unwrap())
+        .collect::<String>()
+        .trim()
+        .to_string()
+}
+
+#[nvim_oxi::test]
+fn cancelled_open_many_clears_prompt() {
+    nvim_oxi::api::command("vsplit").unwrap();
+    let mut opts = Dictionary::new();
+    opts.insert(
+        "paths",
+        Array::from_iter(["winpick_first", "winpick_second"]),
+    );
+    // Typed ahead, cancels the first prompt
+    nvim_oxi::api::call_function::<_, i64>("feedkeys", ("\x1b", "n")).unwrap();
+    nvim_winpick_core::pick_open_many(Some(opts.into()));
+    assert!(!cmdline().contains("[nvim_winpick]"), "{}", cmdline());
+    assert_eq!(
+        2,
+        nvim_oxi::api::get_current_tabpage()
+            .list_wins()
+            .unwrap()
+            .count()
+    );
+}
@@ -0,0 +1,61 @@
+use nvim_oxi::api::Window;
+
+/// Three side by side windows with the first one focused, in tab order, which is label order
+fn three_windows() -> Vec<Window> {
+    let original = nvim_oxi::api::get_current_win();
+    nvim_oxi::api::command("vsplit | vsplit").unwrap();
+    nvim_oxi::api::set_current_win(&original).unwrap();
+    nvim_oxi::api::get_current_tabpage()
+        .list_wins()
+        .unwrap()
+        .collect()
+}
+
+/// `:normal` runs the keys right away, a pick that draws is cancelled once they run out
+fn normal(keys: &str) {
+    nvim_oxi::api::command(&format!(r#"execute "normal {keys}""#)).unwrap();
+}
+
+#[nvim_oxi::test]
+fn counted_operator_focuses_labelled_window() {
+    nvim_winpick_core::setup(None);
+    let windows = three_windows();
+    normal(r"3\<Plug>(winpick-operator)f");
+    assert_eq!(windows[2], nvim_oxi::api::get_current_win());
+}
+
+#[nvim_oxi::test]
+fn counted_operator_closes_labelled_window() {
+    nvim_winpick_core::setup(None);
+    let windows = three_windows();
+    normal(r"2\<Plug>(winpick-operator)c");
+    assert!(!windows[1].is_valid());
+    assert!(windows[0].is_valid() && windows[2].is_valid());
+}
+
+#[nvim_oxi::test]
+fn uncounted_operator_draws() {
+    nvim_winpick_core::setup(None);
+    let windows = three_windows();
+    // Nothing's left to pick with once the keys run out
+    normal(r"\<Plug>(winpick-operator)c");
+    assert!(windows.iter().all(Window::is_valid));
+}
+
+#[nvim_oxi::test]
+fn operator_runs_nothing_on_unknown_key() {
+    nvim_winpick_core::setup(None);
+    let windows = three_windows();
+    normal(r"2\<Plug>(winpick-operator)x");
+    assert!(windows.iter().all(Window::is_valid));
+    assert_eq!(windows[0], nvim_oxi::api::get_current_win());
+}
+
+#[nvim_oxi::test]
+fn operator_cancelled_with_escape() {
+    nvim_winpick_core::setup(None);
+    let windows = three_windows();
+    normal(r"2\<Plug>(winpick-operator)\<Esc>");
+    assert!(windows.iter().all(Window::is_valid));
+    assert_eq!(windows[0], nvim_oxi::api::get_current_win());
+}
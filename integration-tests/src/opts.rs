@@ -1,6 +1,8 @@
+use nvim_oxi::conversion::FromObject;
 use nvim_oxi::{Array, Dictionary};
 use nvim_winpick_core::{
-    Hint, OpenOverOpts, OpenRelativeOpts, OpenSplitOpts, Opts, safe_parse_opts,
+    CloseOpts, DockFloatOpts, Hint, MoveToTabOpts, OpenManyOpts, OpenOverOpts, OpenRelativeOpts,
+    OpenSplitOpts, Opts, PlaceRelativeOpts, RepeatOpts, UnstashOpts, safe_parse_opts,
 };
 
 #[nvim_oxi::test]
@@ -25,7 +27,7 @@ fn set_global_opts_replaces_default() {
 }
 
 #[nvim_oxi::test]
-fn set_global_opts_fails_if_tried_twice() {
+fn set_global_opts_can_be_rerun() {
     let mut dict = Dictionary::new();
     dict.insert("selection_chars", "abcdefg");
     let obj = dict.into();
@@ -39,8 +41,113 @@ fn set_global_opts_fails_if_tried_twice() {
     dict.insert("selection_chars", "loi");
     nvim_winpick_core::setup(Some(dict.into()));
 
-    // Not updated
-    assert_eq!(Opts::default(), modified_defaults);
+    // Replaced, not merged over the previous setup
+    assert_eq!("loi", Opts::default().selection_chars);
+}
+
+#[nvim_oxi::test]
+fn setup_merges_nested_opts_over_defaults() {
+    let builtin = Opts::default();
+    let mut filter_rules = Dictionary::new();
+    filter_rules.insert("include_current_win", false);
+    let mut dict = Dictionary::new();
+    dict.insert("filter_rules", filter_rules);
+    nvim_winpick_core::setup(Some(dict.into()));
+    let merged = Opts::default();
+    assert!(!merged.filter_rules.include_current_win);
+    assert_eq!(
+        builtin.filter_rules.autoselect_one,
+        merged.filter_rules.autoselect_one
+    );
+    assert_eq!(builtin.filter_rules.bo, merged.filter_rules.bo);
+}
+
+#[nvim_oxi::test]
+fn action_opts_merge_over_setup_opts() {
+    let mut dict = Dictionary::new();
+    dict.insert("selection_chars", "abcdefg");
+    nvim_winpick_core::setup(Some(dict.into()));
+    let mut dict = Dictionary::new();
+    dict.insert("relative_preview", true);
+    let parsed = safe_parse_opts(Some(dict.into())).unwrap();
+    assert_eq!("abcdefg", parsed.selection_chars);
+    assert!(parsed.relative_preview);
+}
+
+#[nvim_oxi::test]
+fn profiles_referenced_by_name() {
+    let mut review = Dictionary::new();
+    review.insert("hint", "floating-letter");
+    let mut profiles = Dictionary::new();
+    profiles.insert("review", review);
+    let mut dict = Dictionary::new();
+    dict.insert("selection_chars", "abcdefg");
+    dict.insert("profiles", profiles);
+    nvim_winpick_core::setup(Some(dict.into()));
+    let parsed = safe_parse_opts(Some("review".into())).unwrap();
+    assert_eq!(Hint::FloatingLetter, parsed.hint);
+    // Profiles are merged over the setup opts
+    assert_eq!("abcdefg", parsed.selection_chars);
+    assert_ne!(Hint::FloatingLetter, Opts::default().hint);
+
+    let mut split_opts = Dictionary::new();
+    split_opts.insert("path", "my/path");
+    split_opts.insert("opts", "review");
+    assert!(OpenSplitOpts::parse_obj(split_opts.into()).is_ok());
+}
+
+#[nvim_oxi::test]
+fn setup_takes_action_defaults() {
+    let include_current_win = Opts::default().filter_rules.include_current_win;
+    let mut filter_rules = Dictionary::new();
+    filter_rules.insert("include_current_win", false);
+    let mut swap = Dictionary::new();
+    swap.insert("filter_rules", filter_rules.clone());
+    let mut close = Dictionary::new();
+    close.insert("modified", "skip");
+    close.insert("filter_rules", filter_rules);
+    let mut actions = Dictionary::new();
+    actions.insert("swap", swap);
+    actions.insert("close", close);
+    let mut dict = Dictionary::new();
+    dict.insert("selection_chars", "abcdefg");
+    dict.insert("actions", actions);
+    nvim_winpick_core::setup(Some(dict.into()));
+    // Action defaults don't leak into the general defaults
+    assert_eq!("abcdefg", Opts::default().selection_chars);
+    assert_eq!(
+        include_current_win,
+        Opts::default().filter_rules.include_current_win
+    );
+}
+
+#[nvim_oxi::test]
+fn unknown_action_defaults_rejected() {
+    let mut actions = Dictionary::new();
+    actions.insert("swop", Dictionary::new());
+    let mut dict = Dictionary::new();
+    dict.insert("selection_chars", "abcdefg");
+    dict.insert("actions", actions);
+    nvim_winpick_core::setup(Some(dict.into()));
+    assert_ne!("abcdefg", Opts::default().selection_chars);
+
+    // Unstash doesn't pick, so it can't filter
+    let mut filter_rules = Dictionary::new();
+    filter_rules.insert("include_current_win", false);
+    let mut unstash = Dictionary::new();
+    unstash.insert("filter_rules", filter_rules);
+    let mut actions = Dictionary::new();
+    actions.insert("unstash", unstash);
+    let mut dict = Dictionary::new();
+    dict.insert("selection_chars", "abcdefg");
+    dict.insert("actions", actions);
+    nvim_winpick_core::setup(Some(dict.into()));
+    assert_ne!("abcdefg", Opts::default().selection_chars);
+}
+
+#[nvim_oxi::test]
+fn unknown_profile_rejected() {
+    assert!(safe_parse_opts(Some("missing".into())).is_none());
 }
 
 #[nvim_oxi::test]
@@ -82,6 +189,24 @@ fn unknown_opts_field_rejected() {
     assert!(safe.is_none());
 }
 
+#[nvim_oxi::test]
+fn relative_preview_accepted() {
+    let mut dict = Dictionary::new();
+    dict.insert("relative_preview", true);
+    let obj = dict.into();
+    let parsed = safe_parse_opts(Some(obj)).unwrap();
+    assert!(parsed.relative_preview);
+    assert!(!Opts::default().relative_preview);
+}
+
+#[nvim_oxi::test]
+fn non_bool_relative_preview_rejected() {
+    let mut dict = Dictionary::new();
+    dict.insert("relative_preview", "yes");
+    let obj = dict.into();
+    assert!(safe_parse_opts(Some(obj)).is_none());
+}
+
 #[nvim_oxi::test]
 fn valid_filter_rules_field_accepted() {
     let mut filter_rules = Dictionary::new();
@@ -178,6 +303,34 @@ fn valid_multiselect_accepted() {
     assert_eq!('m', multi.trigger_char);
 }
 
+#[nvim_oxi::test]
+fn multiselect_chars_lowercased() {
+    let mut multiselect = Dictionary::new();
+    multiselect.insert("trigger_char", "M");
+    multiselect.insert("commit_char", "C");
+    let mut dict = Dictionary::new();
+    dict.insert("multiselect", Some(multiselect));
+    let opts = safe_parse_opts(Some(dict.into())).unwrap();
+    let multi = opts.multiselect.unwrap();
+    assert_eq!('c', multi.commit_char);
+    assert_eq!('m', multi.trigger_char);
+}
+
+#[nvim_oxi::test]
+fn empty_multiselect_turns_it_off() {
+    let mut multiselect = Dictionary::new();
+    multiselect.insert("trigger_char", "m");
+    multiselect.insert("commit_char", "c");
+    let mut dict = Dictionary::new();
+    dict.insert("multiselect", multiselect);
+    nvim_winpick_core::setup(Some(dict.into()));
+    assert!(Opts::default().multiselect.is_some());
+    let mut dict = Dictionary::new();
+    dict.insert("multiselect", Dictionary::new());
+    let opts = safe_parse_opts(Some(dict.into())).unwrap();
+    assert!(opts.multiselect.is_none());
+}
+
 #[nvim_oxi::test]
 fn incomplete_multiselect_rejected() {
     let mut multiselect = Dictionary::new();
@@ -212,6 +365,15 @@ fn open_over_opts_valid_with_path() {
     assert!(res.is_ok());
 }
 
+#[nvim_oxi::test]
+fn open_over_opts_valid_with_buf() {
+    let mut dict = Dictionary::new();
+    dict.insert("buf", 0);
+    let obj = dict.into();
+    let res = OpenOverOpts::parse_obj(obj);
+    assert!(res.is_ok());
+}
+
 #[nvim_oxi::test]
 fn open_split_opts_invalid_if_empty() {
     let dict = Dictionary::new();
@@ -229,6 +391,58 @@ fn open_split_opts_valid_with_path() {
     assert!(res.is_ok());
 }
 
+#[nvim_oxi::test]
+fn open_split_opts_valid_with_buf() {
+    let mut dict = Dictionary::new();
+    dict.insert("buf", 0);
+    let obj = dict.into();
+    let res = OpenSplitOpts::parse_obj(obj);
+    assert!(res.is_ok());
+}
+
+#[nvim_oxi::test]
+fn open_split_opts_rejects_both_path_and_buf() {
+    let mut dict = Dictionary::new();
+    dict.insert("path", "my-path");
+    dict.insert("buf", 0);
+    let obj = dict.into();
+    let res = OpenSplitOpts::parse_obj(obj);
+    assert!(res.is_err());
+}
+
+#[nvim_oxi::test]
+fn open_split_opts_valid_with_location() {
+    let mut dict = Dictionary::new();
+    dict.insert("path", "my-path");
+    dict.insert("lnum", 10);
+    dict.insert("col", 4);
+    dict.insert("end_lnum", 12);
+    let obj = dict.into();
+    let res = OpenSplitOpts::parse_obj(obj);
+    assert!(res.is_ok());
+}
+
+#[nvim_oxi::test]
+fn open_split_opts_rejects_col_without_lnum() {
+    let mut dict = Dictionary::new();
+    dict.insert("path", "my-path");
+    dict.insert("col", 4);
+    let obj = dict.into();
+    let res = OpenSplitOpts::parse_obj(obj);
+    assert!(res.is_err());
+}
+
+#[nvim_oxi::test]
+fn open_split_opts_rejects_end_lnum_before_lnum() {
+    let mut dict = Dictionary::new();
+    dict.insert("path", "my-path");
+    dict.insert("lnum", 10);
+    dict.insert("end_lnum", 9);
+    let obj = dict.into();
+    let res = OpenSplitOpts::parse_obj(obj);
+    assert!(res.is_err());
+}
+
 #[nvim_oxi::test]
 fn open_relative_opts_invalid_if_empty() {
     let dict = Dictionary::new();
@@ -246,16 +460,54 @@ fn open_relative_opts_valid_with_path() {
     assert!(res.is_ok());
 }
 
+#[nvim_oxi::test]
+fn open_relative_opts_valid_with_buf() {
+    let mut dict = Dictionary::new();
+    dict.insert("buf", 0);
+    let obj = dict.into();
+    let res = OpenRelativeOpts::parse_obj(obj);
+    assert!(res.is_ok());
+}
+
 #[nvim_oxi::test]
 fn open_relative_opts_allows_any_char_in_relative_chars() {
     let mut dict = Dictionary::new();
     dict.insert("path", "my-path");
-    dict.insert("relative_chars", "ö");
+    dict.insert("relative_chars", "öabcdefgh");
     let obj = dict.into();
     let res = OpenRelativeOpts::parse_obj(obj);
     assert!(res.is_ok());
 }
 
+#[nvim_oxi::test]
+fn open_relative_opts_rejects_fewer_relative_chars_than_positions() {
+    let mut dict = Dictionary::new();
+    dict.insert("path", "my-path");
+    // One short of the nine default positions
+    dict.insert("relative_chars", "abcdefgh");
+    let res = OpenRelativeOpts::parse_obj(dict.clone().into());
+    assert!(res.is_err());
+    dict.insert(
+        "relative_positions",
+        Array::from_iter(["split_top", "split_bottom"]),
+    );
+    let res = OpenRelativeOpts::parse_obj(dict.into());
+    assert!(res.is_ok());
+}
+
+#[nvim_oxi::test]
+fn move_to_tab_opts_skip_relative_chars_unless_relative() {
+    let mut opts = Dictionary::new();
+    opts.insert("selection_chars", "abc");
+    let mut dict = Dictionary::new();
+    dict.insert("opts", opts);
+    let res = MoveToTabOpts::parse_obj(dict.clone().into());
+    assert!(res.is_ok());
+    dict.insert("relative", true);
+    let res = MoveToTabOpts::parse_obj(dict.into());
+    assert!(res.is_err());
+}
+
 #[nvim_oxi::test]
 fn open_relative_opts_rejects_duplicates_in_relative_chars() {
     let mut dict = Dictionary::new();
@@ -265,3 +517,530 @@ fn open_relative_opts_rejects_duplicates_in_relative_chars() {
     let res = OpenRelativeOpts::parse_obj(obj);
     assert!(res.is_err());
 }
+
+#[nvim_oxi::test]
+fn dock_float_opts_valid_if_empty() {
+    let dict = Dictionary::new();
+    let obj = dict.into();
+    let res = DockFloatOpts::parse_obj(obj);
+    assert!(res.is_ok());
+}
+
+#[nvim_oxi::test]
+fn open_relative_opts_valid_with_absolute_size() {
+    let mut dict = Dictionary::new();
+    dict.insert("path", "my-path");
+    dict.insert("size", 10);
+    dict.insert("pick_size", true);
+    let obj = dict.into();
+    let res = OpenRelativeOpts::parse_obj(obj);
+    assert!(res.is_ok());
+}
+
+#[nvim_oxi::test]
+fn open_relative_opts_valid_with_ratio_size() {
+    let mut dict = Dictionary::new();
+    dict.insert("path", "my-path");
+    dict.insert("size", 0.3);
+    let obj = dict.into();
+    let res = OpenRelativeOpts::parse_obj(obj);
+    assert!(res.is_ok());
+}
+
+#[nvim_oxi::test]
+fn open_relative_opts_rejects_out_of_range_ratio_size() {
+    let mut dict = Dictionary::new();
+    dict.insert("path", "my-path");
+    dict.insert("size", 1.5);
+    let obj = dict.into();
+    let res = OpenRelativeOpts::parse_obj(obj);
+    assert!(res.is_err());
+}
+
+#[nvim_oxi::test]
+fn open_split_opts_rejects_non_positive_size() {
+    let mut dict = Dictionary::new();
+    dict.insert("path", "my-path");
+    dict.insert("size", 0);
+    let obj = dict.into();
+    let res = OpenSplitOpts::parse_obj(obj);
+    assert!(res.is_err());
+}
+
+#[nvim_oxi::test]
+fn open_relative_opts_valid_with_relative_keys() {
+    let mut dict = Dictionary::new();
+    dict.insert("path", "my-path");
+    let mut keys = Dictionary::new();
+    keys.insert("corner_top_left", "y");
+    keys.insert("float", "v");
+    keys.insert("new_tab", "t");
+    dict.insert("relative_keys", keys);
+    let obj = dict.into();
+    let res = OpenRelativeOpts::parse_obj(obj);
+    assert!(res.is_ok());
+}
+
+#[nvim_oxi::test]
+fn open_relative_opts_rejects_unknown_position_in_relative_keys() {
+    let mut dict = Dictionary::new();
+    dict.insert("path", "my-path");
+    let mut keys = Dictionary::new();
+    keys.insert("middle", "m");
+    dict.insert("relative_keys", keys);
+    let obj = dict.into();
+    let res = OpenRelativeOpts::parse_obj(obj);
+    assert!(res.is_err());
+}
+
+#[nvim_oxi::test]
+fn open_relative_opts_rejects_duplicates_in_relative_keys() {
+    let mut dict = Dictionary::new();
+    dict.insert("path", "my-path");
+    let mut keys = Dictionary::new();
+    keys.insert("center", "z");
+    keys.insert("float", "Z");
+    dict.insert("relative_keys", keys);
+    let obj = dict.into();
+    let res = OpenRelativeOpts::parse_obj(obj);
+    assert!(res.is_err());
+}
+
+#[nvim_oxi::test]
+fn open_relative_opts_rejects_relative_keys_overlapping_selection_chars() {
+    let mut dict = Dictionary::new();
+    dict.insert("path", "my-path");
+    let mut keys = Dictionary::new();
+    keys.insert("split_left", "h");
+    keys.insert("split_right", "l");
+    dict.insert("relative_keys", keys);
+    let mut opts = Dictionary::new();
+    opts.insert("selection_chars", "ABCL");
+    dict.insert("opts", opts);
+    let obj = dict.into();
+    let res = OpenRelativeOpts::parse_obj(obj);
+    assert!(res.is_err());
+}
+
+#[nvim_oxi::test]
+fn open_relative_opts_valid_with_relative_positions() {
+    let mut dict = Dictionary::new();
+    dict.insert("path", "my-path");
+    dict.insert("relative_chars", "tvbh");
+    dict.insert(
+        "relative_positions",
+        Array::from_iter(["split_top", "split_right", "split_bottom", "split_left"]),
+    );
+    let obj = dict.into();
+    let res = OpenRelativeOpts::parse_obj(obj);
+    assert!(res.is_ok());
+}
+
+#[nvim_oxi::test]
+fn open_relative_opts_rejects_relative_positions_chars_overlapping_selection_chars() {
+    let mut dict = Dictionary::new();
+    dict.insert("path", "my-path");
+    dict.insert("relative_chars", "kljh");
+    dict.insert(
+        "relative_positions",
+        Array::from_iter(["split_top", "split_right", "split_bottom", "split_left"]),
+    );
+    let obj = dict.into();
+    let res = OpenRelativeOpts::parse_obj(obj);
+    assert!(res.is_err());
+}
+
+#[nvim_oxi::test]
+fn open_relative_opts_rejects_unknown_relative_positions() {
+    let mut dict = Dictionary::new();
+    dict.insert("path", "my-path");
+    dict.insert(
+        "relative_positions",
+        Array::from_iter(["split_top", "middle"]),
+    );
+    let obj = dict.into();
+    let res = OpenRelativeOpts::parse_obj(obj);
+    assert!(res.is_err());
+}
+
+#[nvim_oxi::test]
+fn open_relative_opts_rejects_relative_positions_with_keys() {
+    let mut dict = Dictionary::new();
+    dict.insert("path", "my-path");
+    dict.insert("relative_positions", Array::from_iter(["split_top"]));
+    let mut keys = Dictionary::new();
+    keys.insert("split_top", "t");
+    dict.insert("relative_keys", keys);
+    let obj = dict.into();
+    let res = OpenRelativeOpts::parse_obj(obj);
+    assert!(res.is_err());
+}
+
+#[nvim_oxi::test]
+fn open_relative_opts_rejects_both_relative_chars_and_keys() {
+    let mut dict = Dictionary::new();
+    dict.insert("path", "my-path");
+    dict.insert("relative_chars", "abc");
+    let mut keys = Dictionary::new();
+    keys.insert("center", "c");
+    dict.insert("relative_keys", keys);
+    let obj = dict.into();
+    let res = OpenRelativeOpts::parse_obj(obj);
+    assert!(res.is_err());
+}
+
+#[nvim_oxi::test]
+fn dock_float_opts_rejects_duplicates_in_relative_chars() {
+    let mut dict = Dictionary::new();
+    dict.insert("relative_chars", "aa");
+    let obj = dict.into();
+    let res = DockFloatOpts::parse_obj(obj);
+    assert!(res.is_err());
+}
+
+#[nvim_oxi::test]
+fn move_to_tab_opts_valid_if_empty() {
+    let dict = Dictionary::new();
+    let obj = dict.into();
+    let res = MoveToTabOpts::parse_obj(obj);
+    assert!(res.is_ok());
+}
+
+#[nvim_oxi::test]
+fn move_to_tab_opts_rejects_unknown_field() {
+    let mut dict = Dictionary::new();
+    dict.insert("relatives", true);
+    let obj = dict.into();
+    let res = MoveToTabOpts::parse_obj(obj);
+    assert!(res.is_err());
+}
+
+#[nvim_oxi::test]
+fn open_many_opts_invalid_if_empty() {
+    let dict = Dictionary::new();
+    let obj = dict.into();
+    let res = OpenManyOpts::parse_obj(obj);
+    assert!(res.is_err());
+}
+
+#[nvim_oxi::test]
+fn open_many_opts_rejects_empty_paths() {
+    let mut dict = Dictionary::new();
+    dict.insert("paths", Array::new());
+    let obj = dict.into();
+    let res = OpenManyOpts::parse_obj(obj);
+    assert!(res.is_err());
+}
+
+#[nvim_oxi::test]
+fn open_many_opts_valid_with_paths() {
+    let mut paths = Array::new();
+    paths.push("my-path");
+    paths.push("my-other-path");
+    let mut dict = Dictionary::new();
+    dict.insert("paths", paths);
+    let obj = dict.into();
+    let res = OpenManyOpts::parse_obj(obj);
+    assert!(res.is_ok());
+}
+
+#[nvim_oxi::test]
+fn place_relative_opts_valid_if_empty() {
+    let dict = Dictionary::new();
+    let obj = dict.into();
+    let res = PlaceRelativeOpts::parse_obj(obj);
+    assert!(res.is_ok());
+}
+
+#[nvim_oxi::test]
+fn place_relative_opts_valid_with_windows_and_position() {
+    let mut dict = Dictionary::new();
+    dict.insert("source_win", 1000);
+    dict.insert("target_win", 1001);
+    dict.insert("position", "split_right");
+    let obj = dict.into();
+    let res = PlaceRelativeOpts::parse_obj(obj);
+    assert!(res.is_ok());
+}
+
+#[nvim_oxi::test]
+fn place_relative_opts_rejects_unknown_position() {
+    let mut dict = Dictionary::new();
+    dict.insert("position", "split_diagonal");
+    let obj = dict.into();
+    let res = PlaceRelativeOpts::parse_obj(obj);
+    assert!(res.is_err());
+}
+
+#[nvim_oxi::test]
+fn close_opts_valid_if_empty() {
+    let dict = Dictionary::new();
+    let obj = dict.into();
+    let res = CloseOpts::parse_obj(obj);
+    assert!(res.is_ok());
+}
+
+#[nvim_oxi::test]
+fn close_opts_valid_with_all_fields() {
+    let mut dict = Dictionary::new();
+    dict.insert("modified", "skip");
+    dict.insert("delete_buffer", "bwipeout");
+    let mut protect = Dictionary::new();
+    protect.insert("buftype", Array::from_iter(["terminal"]));
+    dict.insert("protect", protect);
+    dict.insert("opts", Dictionary::new());
+    let obj = dict.into();
+    let res = CloseOpts::parse_obj(obj);
+    assert!(res.is_ok());
+}
+
+#[nvim_oxi::test]
+fn close_opts_rejects_unknown_modified_action() {
+    let mut dict = Dictionary::new();
+    dict.insert("modified", "ignore");
+    let obj = dict.into();
+    let res = CloseOpts::parse_obj(obj);
+    assert!(res.is_err());
+}
+
+#[nvim_oxi::test]
+fn close_opts_rejects_unknown_delete_buffer() {
+    let mut dict = Dictionary::new();
+    dict.insert("delete_buffer", "bunload");
+    let obj = dict.into();
+    let res = CloseOpts::parse_obj(obj);
+    assert!(res.is_err());
+}
+
+#[nvim_oxi::test]
+fn unstash_opts_valid_if_empty() {
+    let dict = Dictionary::new();
+    let obj = dict.into();
+    let res = UnstashOpts::parse_obj(obj);
+    assert!(res.is_ok());
+}
+
+#[nvim_oxi::test]
+fn unstash_opts_rejects_non_bool_pick() {
+    let mut dict = Dictionary::new();
+    dict.insert("pick", "yes");
+    let obj = dict.into();
+    let res = UnstashOpts::parse_obj(obj);
+    assert!(res.is_err());
+}
+
+#[nvim_oxi::test]
+fn dot_repeat_accepted() {
+    let mut dict = Dictionary::new();
+    dict.insert("dot_repeat", true);
+    let obj = dict.into();
+    let parsed = safe_parse_opts(Some(obj)).unwrap();
+    assert!(parsed.dot_repeat);
+    assert!(!Opts::default().dot_repeat);
+}
+
+#[nvim_oxi::test]
+fn repeat_opts_valid_if_empty() {
+    let dict = Dictionary::new();
+    let obj = dict.into();
+    let res = RepeatOpts::parse_obj(obj);
+    assert!(res.is_ok());
+}
+
+#[nvim_oxi::test]
+fn repeat_opts_rejects_path_and_buf() {
+    let mut dict = Dictionary::new();
+    dict.insert("path", "my/path");
+    dict.insert("buf", 1);
+    let obj = dict.into();
+    let res = RepeatOpts::parse_obj(obj);
+    assert!(res.is_err());
+}
+
+#[nvim_oxi::test]
+fn setup_registers_winpick_command() {
+    nvim_winpick_core::setup(None);
+    let exists: i64 = nvim_oxi::api::call_function("exists", (":Winpick",)).unwrap();
+    assert_eq!(2, exists);
+}
+
+#[nvim_oxi::test]
+fn setup_default_keymaps_bound() {
+    let mut dict = Dictionary::new();
+    dict.insert("keymaps", "default");
+    nvim_winpick_core::setup(Some(dict.into()));
+    let plug: String =
+        nvim_oxi::api::call_function("maparg", ("<Plug>(winpick-focus)", "n")).unwrap();
+    assert!(!plug.is_empty());
+    let bound: String = nvim_oxi::api::call_function("maparg", ("<leader>wf", "n")).unwrap();
+    assert_eq!("<Plug>(winpick-focus)", bound);
+}
+
+#[nvim_oxi::test]
+fn setup_custom_keymaps_bound() {
+    let mut keymaps = Dictionary::new();
+    keymaps.insert("swap", "<leader>S");
+    keymaps.insert("focus", false);
+    let mut dict = Dictionary::new();
+    dict.insert("keymaps", keymaps);
+    nvim_winpick_core::setup(Some(dict.into()));
+    let bound: String = nvim_oxi::api::call_function("maparg", ("<leader>S", "n")).unwrap();
+    assert_eq!("<Plug>(winpick-swap)", bound);
+    let unbound: String = nvim_oxi::api::call_function("maparg", ("<leader>wf", "n")).unwrap();
+    assert!(unbound.is_empty());
+}
+
+fn maparg(lhs: &str) -> String {
+    nvim_oxi::api::call_function("maparg", (lhs, "n")).unwrap()
+}
+
+#[nvim_oxi::test]
+fn setup_again_unbinds_previous_keymaps() {
+    let mut dict = Dictionary::new();
+    dict.insert("keymaps", "default");
+    nvim_winpick_core::setup(Some(dict.into()));
+    let mut keymaps = Dictionary::new();
+    keymaps.insert("swap", "<leader>S");
+    let mut dict = Dictionary::new();
+    dict.insert("keymaps", keymaps);
+    nvim_winpick_core::setup(Some(dict.into()));
+    assert!(maparg("<leader>wf").is_empty());
+    assert_eq!("<Plug>(winpick-swap)", maparg("<leader>S"));
+    let mut dict = Dictionary::new();
+    dict.insert("keymaps", false);
+    nvim_winpick_core::setup(Some(dict.into()));
+    assert!(maparg("<leader>S").is_empty());
+    // The `<Plug>` mappings stay
+    assert!(!maparg("<Plug>(winpick-swap)").is_empty());
+}
+
+#[nvim_oxi::test]
+fn setup_again_keeps_keys_mapped_since() {
+    let mut dict = Dictionary::new();
+    dict.insert("keymaps", "default");
+    nvim_winpick_core::setup(Some(dict.into()));
+    nvim_oxi::api::command("nnoremap <leader>wf <C-w>w").unwrap();
+    nvim_winpick_core::setup(None);
+    assert_eq!("<C-W>w", maparg("<leader>wf"));
+    assert!(maparg("<leader>ws").is_empty());
+}
+
+#[nvim_oxi::test]
+fn reports_every_invalid_field_with_its_path() {
+    let mut bo = Dictionary::new();
+    bo.insert("filetype", Array::from_iter(["lua", "rust"]));
+    bo.insert("buftype", Array::from_iter([1]));
+    let mut filter_rules = Dictionary::new();
+    filter_rules.insert("include_floatin", false);
+    filter_rules.insert("bo", bo);
+    let mut opts = Dictionary::new();
+    opts.insert("filter_rules", filter_rules);
+    opts.insert("hint", "floating-leter");
+    let mut dict = Dictionary::new();
+    dict.insert("opts", opts);
+    dict.insert("modified", true);
+    let Some(err) = CloseOpts::parse_obj(dict.into()).err() else {
+        panic!("invalid close opts accepted");
+    };
+    let msg = err.to_string();
+    assert!(msg.contains(
+        "`opts.filter_rules.include_floatin`: unknown field, did you mean `include_floating`?"
+    ));
+    assert!(msg.contains("`opts.filter_rules.bo.buftype[1]`: expected a string, got integer"));
+    assert!(
+        msg.contains(
+            "`opts.hint`: unknown value 'floating-leter', did you mean 'floating-letter'?"
+        )
+    );
+    assert!(msg.contains("`modified`: expected one of [prompt, skip, discard], got boolean"));
+}
+
+#[nvim_oxi::test]
+fn open_relative_opts_errors_name_open_relative() {
+    let mut dict = Dictionary::new();
+    dict.insert("path", "my/path");
+    dict.insert("relative_char", "abc");
+    let Some(err) = OpenRelativeOpts::parse_obj(dict.into()).err() else {
+        panic!("unknown field accepted");
+    };
+    let msg = err.to_string();
+    assert!(msg.starts_with("invalid 'open_relative_opts'"));
+    assert!(msg.contains("did you mean `relative_chars`?"));
+}
+
+#[nvim_oxi::test]
+fn setup_rejects_misspelled_nested_fields() {
+    let mut filter_rules = Dictionary::new();
+    filter_rules.insert("include_curent_win", false);
+    let mut swap = Dictionary::new();
+    swap.insert("filter_rules", filter_rules);
+    let mut actions = Dictionary::new();
+    actions.insert("swap", swap);
+    let mut dict = Dictionary::new();
+    dict.insert("selection_chars", "abcdefg");
+    dict.insert("actions", actions);
+    nvim_winpick_core::setup(Some(dict.into()));
+    assert_ne!("abcdefg", Opts::default().selection_chars);
+}
+
+fn health_levels(section: &str) -> Vec<String> {
+    nvim_winpick_core::health()
+        .into_iter()
+        .filter_map(|report| Dictionary::from_object(report).ok())
+        .filter(|report| {
+            report
+                .get("section")
+                .and_then(|obj| String::from_object(obj.clone()).ok())
+                .is_some_and(|name| name == section)
+        })
+        .filter_map(|report| {
+            report
+                .get("level")
+                .and_then(|obj| String::from_object(obj.clone()).ok())
+        })
+        .collect()
+}
+
+#[nvim_oxi::test]
+fn health_reports_setup_status() {
+    assert_eq!(vec!["info"], health_levels("setup"));
+    nvim_winpick_core::setup(None);
+    assert_eq!(vec!["ok"], health_levels("setup"));
+    let mut dict = Dictionary::new();
+    dict.insert("hnt", "floating-letter");
+    nvim_winpick_core::setup(Some(dict.into()));
+    assert_eq!(vec!["error"], health_levels("setup"));
+}
+
+#[nvim_oxi::test]
+fn health_reports_ambiguous_selection_chars() {
+    let mut dict = Dictionary::new();
+    dict.insert("selection_chars", "abcA");
+    dict.insert("hint", "floating-letter");
+    nvim_winpick_core::setup(Some(dict.into()));
+    assert_eq!(vec!["warn"], health_levels("opts"));
+}
+
+/// The generated copies are committed, rewrite them with `nvim -l schema.lua`
+#[nvim_oxi::test]
+fn committed_schema_is_up_to_date() {
+    let root = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("..");
+    for (format, path) in [
+        ("lua", root.join("lua/nvim_winpick/types.lua")),
+        ("json", root.join("nvim_winpick.schema.json")),
+    ] {
+        let generated = nvim_winpick_core::schema(Some(format.into())).unwrap();
+        let committed = std::fs::read_to_string(&path).unwrap();
+        assert_eq!(
+            committed,
+            generated,
+            "{} is out of date, rewrite it with `nvim -l schema.lua`",
+            path.display()
+        );
+    }
+}
+
+#[nvim_oxi::test]
+fn schema_rejects_unknown_format() {
+    assert_eq!(None, nvim_winpick_core::schema(Some("yaml".into())));
+}
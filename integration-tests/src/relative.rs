@@ -0,0 +1,71 @@
+use nvim_oxi::api::Window;
+use nvim_oxi::{Dictionary, Object};
+
+/// Splits the current window vertically, returns the original window and the new one with
+/// the original focused
+fn two_columns() -> (Window, Window) {
+    let original = nvim_oxi::api::get_current_win();
+    nvim_oxi::api::command("vsplit").unwrap();
+    let other = nvim_oxi::api::get_current_win();
+    nvim_oxi::api::set_current_win(&original).unwrap();
+    (original, other)
+}
+
+/// Opens a scratch buffer below the picked window, which is the only candidate once the current
+/// window is filtered out, `x` is typed to pick the position
+fn split_below_other(focus_new: bool) -> Object {
+    let buf = nvim_oxi::api::create_buf(false, true).unwrap();
+    let mut keys = Dictionary::new();
+    keys.insert("split_bottom", "x");
+    let mut filter_rules = Dictionary::new();
+    filter_rules.insert("include_current_win", false);
+    let mut opts = Dictionary::new();
+    opts.insert("filter_rules", filter_rules);
+    let mut dict = Dictionary::new();
+    dict.insert("buf", buf.handle());
+    dict.insert("focus_new", focus_new);
+    dict.insert("relative_keys", keys);
+    dict.insert("opts", opts);
+    dict.into()
+}
+
+fn type_keys(keys: &str) {
+    nvim_oxi::api::call_function::<_, i64>("feedkeys", (keys, "n")).unwrap();
+}
+
+fn new_windows(before: &[Window]) -> Vec<Window> {
+    nvim_oxi::api::list_wins()
+        .filter(|win| !before.contains(win))
+        .collect()
+}
+
+fn col(win: &Window) -> usize {
+    win.get_position().unwrap().1
+}
+
+#[nvim_oxi::test]
+fn relative_split_without_focus_splits_picked_window() {
+    let (original, other) = two_columns();
+    let before: Vec<Window> = nvim_oxi::api::list_wins().collect();
+    type_keys("x");
+    nvim_winpick_core::pick_win_relative(Some(split_below_other(false)));
+    let new = new_windows(&before);
+    assert_eq!(1, new.len());
+    assert_eq!(col(&other), col(&new[0]));
+    assert_eq!(original, nvim_oxi::api::get_current_win());
+}
+
+#[nvim_oxi::test]
+fn repeated_relative_split_splits_picked_window() {
+    let (original, other) = two_columns();
+    type_keys("x");
+    nvim_winpick_core::pick_win_relative(Some(split_below_other(false)));
+    let before: Vec<Window> = nvim_oxi::api::list_wins().collect();
+    // Repeats from whichever window is current, the pick is what decides where it opens
+    nvim_oxi::api::set_current_win(&original).unwrap();
+    nvim_winpick_core::repeat_last(None);
+    let new = new_windows(&before);
+    assert_eq!(1, new.len());
+    assert_eq!(col(&other), col(&new[0]));
+    assert_eq!(original, nvim_oxi::api::get_current_win());
+}
@@ -0,0 +1,63 @@
+use nvim_oxi::api::opts::OptionOpts;
+use nvim_oxi::{Dictionary, Object};
+
+fn operatorfunc() -> String {
+    nvim_oxi::api::get_option_value("operatorfunc", &OptionOpts::default()).unwrap()
+}
+
+#[nvim_oxi::test]
+fn dot_repeat_waits_for_the_queued_operator() {
+    let mut opts = Dictionary::new();
+    opts.insert("dot_repeat", true);
+    // The only window is autoselected
+    nvim_winpick_core::pick_focus_window(Some(opts.into()));
+    // Nothing repeats until the fed `g@_` runs, so a failed `g@_` can't swallow a later `.`
+    assert_eq!("v:lua.require'nvim_winpick'.prime_repeat", operatorfunc());
+    nvim_winpick_core::prime_repeat();
+    assert_eq!(
+        "v:lua.require'nvim_winpick'.repeat_operator",
+        operatorfunc()
+    );
+}
+
+#[nvim_oxi::test]
+fn dot_repeat_primes_in_an_empty_buffer() {
+    let mut opts = Dictionary::new();
+    opts.insert("dot_repeat", true);
+    nvim_winpick_core::pick_focus_window(Some(opts.into()));
+    // Runs the fed `g@_`, which can't move anywhere in the empty buffer
+    nvim_oxi::api::command("call feedkeys('', 'x')").unwrap();
+    assert_eq!(
+        "v:lua.require'nvim_winpick'.repeat_operator",
+        operatorfunc()
+    );
+}
+
+/// Replaces `vim.notify` with one that collects the messages, read with [`notifications`]
+fn collect_notifications() {
+    nvim_oxi::api::command(
+        "lua _G.winpick_notes = {}; vim.notify = function(msg) table.insert(_G.winpick_notes, msg) end",
+    )
+    .unwrap();
+}
+
+fn notifications() -> Vec<String> {
+    nvim_oxi::api::call_function("luaeval", ("_G.winpick_notes",)).unwrap()
+}
+
+#[nvim_oxi::test]
+fn dot_repeat_reports_actions_it_cant_repeat() {
+    collect_notifications();
+    let mut opts = Dictionary::new();
+    opts.insert("dot_repeat", true);
+    let mut only_opts = Dictionary::new();
+    only_opts.insert("opts", opts);
+    let only_opts: Object = only_opts.into();
+    // The only window is autoselected and kept
+    let _ = nvim_winpick_core::pick_only_windows(Some(only_opts.clone()));
+    let _ = nvim_winpick_core::pick_only_windows(Some(only_opts));
+    let notes = notifications();
+    assert_eq!(1, notes.len(), "{notes:?}");
+    assert!(notes[0].contains("`.` doesn't repeat only"));
+    assert_ne!("v:lua.require'nvim_winpick'.prime_repeat", operatorfunc());
+}
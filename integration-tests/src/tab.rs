@@ -0,0 +1,17 @@
+use nvim_oxi::Dictionary;
+
+#[nvim_oxi::test]
+fn moving_the_last_split_of_a_tab_is_refused() {
+    nvim_oxi::api::command("tabnew").unwrap();
+    let tab = nvim_oxi::api::get_current_tabpage();
+    let window = nvim_oxi::api::get_current_win();
+    let mut count = Dictionary::new();
+    count.insert("count", 1);
+    let mut opts = Dictionary::new();
+    opts.insert("opts", count);
+    // The count picks the only window, the tab is never prompted for
+    nvim_winpick_core::pick_move_to_tab(Some(opts.into()));
+    assert_eq!(2, nvim_oxi::api::list_tabpages().count());
+    assert!(window.is_valid());
+    assert_eq!(tab, window.get_tabpage().unwrap());
+}
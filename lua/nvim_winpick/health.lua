@@ -0,0 +1,24 @@
+-- `:checkhealth nvim_winpick`, the checks themselves live in the library
+local M = {}
+
+M.check = function()
+	vim.health.start("nvim_winpick")
+	local ok, winpick = pcall(require, "nvim_winpick")
+	if not ok then
+		vim.health.error("failed to load the library: " .. tostring(winpick), {
+			"build it with `cargo b -r -p nvim_winpick`, or use a prebuilt branch, see Install.md",
+			"a library built for a newer neovim can fail to load on an older one",
+		})
+		return
+	end
+	local section = nil
+	for _, report in ipairs(winpick.health()) do
+		if report.section ~= section then
+			section = report.section
+			vim.health.start("nvim_winpick: " .. section)
+		end
+		vim.health[report.level](report.msg, report.advice)
+	end
+end
+
+return M
@@ -0,0 +1,308 @@
+---@meta
+-- Generated with `require("nvim_winpick").schema("lua")`, don't edit by hand
+
+---@class nvim_winpick.SetupOpts
+---@field filter_rules? nvim_winpick.FilterRules
+---@field selection_chars? string
+---@field hint? nvim_winpick.Hint
+---@field multiselect? nvim_winpick.Multiselect
+---@field relative_preview? boolean
+---@field dot_repeat? boolean
+---@field hooks? nvim_winpick.Hooks
+---@field profiles? table<string, nvim_winpick.Opts>
+---@field actions? nvim_winpick.ActionDefaults
+---@field keymaps? boolean|"default"|nvim_winpick.Keymaps
+
+---@class nvim_winpick.FilterRules
+---@field autoselect_one? boolean
+---@field include_current_win? boolean
+---@field include_floating? boolean
+---@field include_unfocusable_windows? boolean
+---@field bo? nvim_winpick.Bo
+---@field file_path_contains? string[]
+---@field file_name_contains? string[]
+
+---@class nvim_winpick.Bo
+---@field filetype? string[]
+---@field buftype? string[]
+
+---@alias nvim_winpick.Hint "floating-big-letter"|"floating-letter"
+
+---@class nvim_winpick.Multiselect
+---@field trigger_char? string
+---@field commit_char? string
+
+---@class nvim_winpick.Hooks
+---@field on_pick_start? nvim_winpick.Hook
+---@field on_pick? nvim_winpick.Hook
+---@field on_cancel? nvim_winpick.Hook
+---@field on_action_done? nvim_winpick.Hook
+
+---@alias nvim_winpick.Hook fun(event: table)
+
+---@class nvim_winpick.Opts
+---@field filter_rules? nvim_winpick.FilterRules
+---@field selection_chars? string
+---@field hint? nvim_winpick.Hint
+---@field multiselect? nvim_winpick.Multiselect
+---@field relative_preview? boolean
+---@field dot_repeat? boolean
+---@field hooks? nvim_winpick.Hooks
+---@field count? integer
+
+---@class nvim_winpick.ActionDefaults
+---@field focus? nvim_winpick.Opts
+---@field swap? nvim_winpick.Opts
+---@field stash? nvim_winpick.Opts
+---@field float? nvim_winpick.Opts
+---@field close? nvim_winpick.CloseDefaults
+---@field only? nvim_winpick.CloseDefaults
+---@field split? nvim_winpick.OpenSplitDefaults
+---@field over? nvim_winpick.OpenOverDefaults
+---@field relative? nvim_winpick.OpenRelativeDefaults
+---@field place? nvim_winpick.PlaceRelativeDefaults
+---@field open_many? nvim_winpick.OpenManyDefaults
+---@field dock? nvim_winpick.DockFloatDefaults
+---@field tab? nvim_winpick.MoveToTabDefaults
+---@field unstash? nvim_winpick.UnstashOpts
+
+---@class nvim_winpick.CloseDefaults
+---@field modified? nvim_winpick.ModifiedAction
+---@field protect? nvim_winpick.Bo
+---@field delete_buffer? nvim_winpick.DeleteBuffer
+---@field opts? string|nvim_winpick.Opts
+---@field filter_rules? nvim_winpick.FilterRules
+---@field selection_chars? string
+---@field hint? nvim_winpick.Hint
+---@field multiselect? nvim_winpick.Multiselect
+---@field relative_preview? boolean
+---@field dot_repeat? boolean
+---@field hooks? nvim_winpick.Hooks
+
+---@alias nvim_winpick.ModifiedAction "prompt"|"skip"|"discard"
+
+---@alias nvim_winpick.DeleteBuffer "keep"|"bdelete"|"bwipeout"
+
+---@class nvim_winpick.OpenSplitDefaults
+---@field path? string
+---@field buf? integer
+---@field lnum? integer
+---@field col? integer
+---@field end_lnum? integer
+---@field size? nvim_winpick.SplitSize
+---@field pick_size? boolean
+---@field focus_new? boolean
+---@field vertical? boolean
+---@field opts? string|nvim_winpick.Opts
+---@field filter_rules? nvim_winpick.FilterRules
+---@field selection_chars? string
+---@field hint? nvim_winpick.Hint
+---@field multiselect? nvim_winpick.Multiselect
+---@field relative_preview? boolean
+---@field dot_repeat? boolean
+---@field hooks? nvim_winpick.Hooks
+
+---@alias nvim_winpick.SplitSize integer|number
+
+---@class nvim_winpick.OpenOverDefaults
+---@field path? string
+---@field buf? integer
+---@field lnum? integer
+---@field col? integer
+---@field end_lnum? integer
+---@field opts? string|nvim_winpick.Opts
+---@field focus_new? boolean
+---@field filter_rules? nvim_winpick.FilterRules
+---@field selection_chars? string
+---@field hint? nvim_winpick.Hint
+---@field multiselect? nvim_winpick.Multiselect
+---@field relative_preview? boolean
+---@field dot_repeat? boolean
+---@field hooks? nvim_winpick.Hooks
+
+---@class nvim_winpick.OpenRelativeDefaults
+---@field path? string
+---@field buf? integer
+---@field lnum? integer
+---@field col? integer
+---@field end_lnum? integer
+---@field opts? string|nvim_winpick.Opts
+---@field relative_chars? string
+---@field relative_keys? nvim_winpick.RelativeKeys
+---@field relative_positions? nvim_winpick.Position[]
+---@field size? nvim_winpick.SplitSize
+---@field pick_size? boolean
+---@field focus_new? boolean
+---@field filter_rules? nvim_winpick.FilterRules
+---@field selection_chars? string
+---@field hint? nvim_winpick.Hint
+---@field multiselect? nvim_winpick.Multiselect
+---@field relative_preview? boolean
+---@field dot_repeat? boolean
+---@field hooks? nvim_winpick.Hooks
+
+---@alias nvim_winpick.RelativeKeys table<nvim_winpick.Position, string>
+
+---@alias nvim_winpick.Position "full_top"|"full_right"|"full_bottom"|"full_left"|"split_top"|"split_right"|"split_bottom"|"split_left"|"center"|"corner_top_left"|"corner_top_right"|"corner_bottom_left"|"corner_bottom_right"|"float"|"new_tab"|"replace_keep_alternate"
+
+---@class nvim_winpick.PlaceRelativeDefaults
+---@field source_win? integer
+---@field target_win? integer
+---@field position? nvim_winpick.Position
+---@field opts? string|nvim_winpick.Opts
+---@field focus_new? boolean
+---@field relative_chars? string
+---@field relative_keys? nvim_winpick.RelativeKeys
+---@field relative_positions? nvim_winpick.Position[]
+---@field filter_rules? nvim_winpick.FilterRules
+---@field selection_chars? string
+---@field hint? nvim_winpick.Hint
+---@field multiselect? nvim_winpick.Multiselect
+---@field relative_preview? boolean
+---@field dot_repeat? boolean
+---@field hooks? nvim_winpick.Hooks
+
+---@class nvim_winpick.OpenManyDefaults
+---@field paths? string[]
+---@field opts? string|nvim_winpick.Opts
+---@field relative_chars? string
+---@field relative_keys? nvim_winpick.RelativeKeys
+---@field relative_positions? nvim_winpick.Position[]
+---@field size? nvim_winpick.SplitSize
+---@field pick_size? boolean
+---@field focus_new? boolean
+---@field filter_rules? nvim_winpick.FilterRules
+---@field selection_chars? string
+---@field hint? nvim_winpick.Hint
+---@field multiselect? nvim_winpick.Multiselect
+---@field relative_preview? boolean
+---@field dot_repeat? boolean
+---@field hooks? nvim_winpick.Hooks
+
+---@class nvim_winpick.DockFloatDefaults
+---@field opts? string|nvim_winpick.Opts
+---@field focus_new? boolean
+---@field relative_chars? string
+---@field relative_keys? nvim_winpick.RelativeKeys
+---@field relative_positions? nvim_winpick.Position[]
+---@field filter_rules? nvim_winpick.FilterRules
+---@field selection_chars? string
+---@field hint? nvim_winpick.Hint
+---@field multiselect? nvim_winpick.Multiselect
+---@field relative_preview? boolean
+---@field dot_repeat? boolean
+---@field hooks? nvim_winpick.Hooks
+
+---@class nvim_winpick.MoveToTabDefaults
+---@field opts? string|nvim_winpick.Opts
+---@field focus_new? boolean
+---@field relative? boolean
+---@field relative_chars? string
+---@field relative_keys? nvim_winpick.RelativeKeys
+---@field relative_positions? nvim_winpick.Position[]
+---@field filter_rules? nvim_winpick.FilterRules
+---@field selection_chars? string
+---@field hint? nvim_winpick.Hint
+---@field multiselect? nvim_winpick.Multiselect
+---@field relative_preview? boolean
+---@field dot_repeat? boolean
+---@field hooks? nvim_winpick.Hooks
+
+---@class nvim_winpick.UnstashOpts
+---@field pick? boolean
+---@field focus_new? boolean
+
+---@class nvim_winpick.Keymaps
+---@field focus? string|boolean
+---@field swap? string|boolean
+---@field close? string|boolean
+---@field only? string|boolean
+---@field stash? string|boolean
+---@field unstash? string|boolean
+---@field float? string|boolean
+---@field dock? string|boolean
+---@field tab? string|boolean
+---@field undo? string|boolean
+---@field redo? string|boolean
+---@field repeat? string|boolean
+---@field operator? string|boolean
+
+---@class nvim_winpick.OpenSplitOpts
+---@field path? string
+---@field buf? integer
+---@field lnum? integer
+---@field col? integer
+---@field end_lnum? integer
+---@field size? nvim_winpick.SplitSize
+---@field pick_size? boolean
+---@field focus_new? boolean
+---@field vertical? boolean
+---@field opts? string|nvim_winpick.Opts
+
+---@class nvim_winpick.OpenOverOpts
+---@field path? string
+---@field buf? integer
+---@field lnum? integer
+---@field col? integer
+---@field end_lnum? integer
+---@field opts? string|nvim_winpick.Opts
+---@field focus_new? boolean
+
+---@class nvim_winpick.OpenRelativeOpts
+---@field path? string
+---@field buf? integer
+---@field lnum? integer
+---@field col? integer
+---@field end_lnum? integer
+---@field opts? string|nvim_winpick.Opts
+---@field relative_chars? string
+---@field relative_keys? nvim_winpick.RelativeKeys
+---@field relative_positions? nvim_winpick.Position[]
+---@field size? nvim_winpick.SplitSize
+---@field pick_size? boolean
+---@field focus_new? boolean
+
+---@class nvim_winpick.CloseOpts
+---@field modified? nvim_winpick.ModifiedAction
+---@field protect? nvim_winpick.Bo
+---@field delete_buffer? nvim_winpick.DeleteBuffer
+---@field opts? string|nvim_winpick.Opts
+
+---@class nvim_winpick.RepeatOpts
+---@field path? string
+---@field buf? integer
+
+---@class nvim_winpick.PlaceRelativeOpts
+---@field source_win? integer
+---@field target_win? integer
+---@field position? nvim_winpick.Position
+---@field opts? string|nvim_winpick.Opts
+---@field focus_new? boolean
+---@field relative_chars? string
+---@field relative_keys? nvim_winpick.RelativeKeys
+---@field relative_positions? nvim_winpick.Position[]
+
+---@class nvim_winpick.OpenManyOpts
+---@field paths? string[]
+---@field opts? string|nvim_winpick.Opts
+---@field relative_chars? string
+---@field relative_keys? nvim_winpick.RelativeKeys
+---@field relative_positions? nvim_winpick.Position[]
+---@field size? nvim_winpick.SplitSize
+---@field pick_size? boolean
+---@field focus_new? boolean
+
+---@class nvim_winpick.DockFloatOpts
+---@field opts? string|nvim_winpick.Opts
+---@field focus_new? boolean
+---@field relative_chars? string
+---@field relative_keys? nvim_winpick.RelativeKeys
+---@field relative_positions? nvim_winpick.Position[]
+
+---@class nvim_winpick.MoveToTabOpts
+---@field opts? string|nvim_winpick.Opts
+---@field focus_new? boolean
+---@field relative? boolean
+---@field relative_chars? string
+---@field relative_keys? nvim_winpick.RelativeKeys
+---@field relative_positions? nvim_winpick.Position[]
@@ -0,0 +1,296 @@
+use std::borrow::Cow;
+use std::cell::RefCell;
+use std::fmt;
+use std::marker::PhantomData;
+
+use nvim_oxi::conversion::FromObject;
+use nvim_oxi::{Dictionary, Object, ObjectKind};
+use schemars::{json_schema, JsonSchema, Schema, SchemaGenerator};
+use serde::de::{DeserializeOwned, MapAccess, Visitor};
+use serde::{Deserialize, Deserializer};
+
+use crate::opts::{
+    obj_to_dict, CloseFields, DockFloatFields, MoveToTabFields, OpenManyFields, OpenOverFields,
+    OpenRelativeFields, OpenSplitFields, Opts, OptsFields, PlaceRelativeFields, UnstashFields,
+};
+use crate::validate::{self, Issues};
+
+/// How an action's opts table is shaped
+#[derive(Clone, Copy)]
+enum Shape {
+    /// Takes [`Opts`] directly
+    General,
+    /// Has its own fields, with [`Opts`] under `opts`
+    Nested(Fields),
+    /// Only has its own fields
+    Own(Fields),
+}
+
+/// An action's own fields
+#[derive(Clone, Copy)]
+struct Fields {
+    /// Checks the fields as given, how they combine is checked once the action runs
+    check: fn(Object, &mut Issues, &str),
+    /// The schema of its defaults in `actions`
+    schema: fn(&mut SchemaGenerator) -> Schema,
+}
+
+const fn nested<F: DeserializeOwned + JsonSchema>() -> Shape {
+    Shape::Nested(Fields {
+        check: check_fields::<F>,
+        schema: SchemaGenerator::subschema_for::<Defaults<F>>,
+    })
+}
+
+const fn own<F: DeserializeOwned + JsonSchema>() -> Shape {
+    Shape::Own(Fields {
+        check: check_fields::<F>,
+        schema: SchemaGenerator::subschema_for::<F>,
+    })
+}
+
+fn check_fields<F: DeserializeOwned>(object: Object, issues: &mut Issues, path: &str) {
+    let _ = issues.deserialize::<F>(object, path);
+}
+
+/// Names usable in `setup({ actions = { <name> = {...} } })`
+const ACTIONS: [(&str, Shape); 14] = [
+    ("focus", Shape::General),
+    ("swap", Shape::General),
+    ("stash", Shape::General),
+    ("float", Shape::General),
+    ("close", nested::<CloseFields>()),
+    ("only", nested::<CloseFields>()),
+    ("split", nested::<OpenSplitFields>()),
+    ("over", nested::<OpenOverFields>()),
+    ("relative", nested::<OpenRelativeFields>()),
+    ("place", nested::<PlaceRelativeFields>()),
+    ("open_many", nested::<OpenManyFields>()),
+    ("dock", nested::<DockFloatFields>()),
+    ("tab", nested::<MoveToTabFields>()),
+    ("unstash", own::<UnstashFields>()),
+];
+
+const ACTION_NAMES: [&str; ACTIONS.len()] = {
+    let mut names = [""; ACTIONS.len()];
+    let mut i = 0;
+    while i < ACTIONS.len() {
+        names[i] = ACTIONS[i].0;
+        i += 1;
+    }
+    names
+};
+
+thread_local! {
+    /// Kept as tables, they're merged with the call-site opts before parsing
+    static ACTION_DEFAULTS: RefCell<Vec<(&'static str, Dictionary)>> =
+        const { RefCell::new(Vec::new()) };
+}
+
+/// The `actions` table of `setup`, each action's defaults as given
+pub(crate) struct ActionDefaults(Vec<(&'static str, Shape, Object)>);
+
+impl<'de> Deserialize<'de> for ActionDefaults {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct DefaultsVisitor;
+
+        impl<'de> Visitor<'de> for DefaultsVisitor {
+            type Value = ActionDefaults;
+
+            fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str("a table of action names to their defaults")
+            }
+
+            fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+                let mut actions = Vec::new();
+                while let Some((action, defaults)) = map.next_entry::<Action, Object>()? {
+                    actions.push((action.0 .0, action.0 .1, defaults));
+                }
+                Ok(ActionDefaults(actions))
+            }
+        }
+
+        deserializer.deserialize_map(DefaultsVisitor)
+    }
+}
+
+impl JsonSchema for ActionDefaults {
+    fn schema_name() -> Cow<'static, str> {
+        "ActionDefaults".into()
+    }
+
+    fn json_schema(generator: &mut SchemaGenerator) -> Schema {
+        let properties: serde_json::Map<String, serde_json::Value> = ACTIONS
+            .iter()
+            .map(|(name, shape)| {
+                let schema = match shape {
+                    Shape::General => generator.subschema_for::<OptsFields>(),
+                    Shape::Nested(fields) | Shape::Own(fields) => (fields.schema)(generator),
+                };
+                ((*name).to_string(), schema.to_value())
+            })
+            .collect();
+        json_schema!({
+            "type": "object",
+            "properties": properties,
+            "additionalProperties": false,
+        })
+    }
+}
+
+/// One of [`ACTIONS`]
+struct Action(&'static (&'static str, Shape));
+
+impl<'de> Deserialize<'de> for Action {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct ActionVisitor;
+
+        impl Visitor<'_> for ActionVisitor {
+            type Value = Action;
+
+            fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str("an action name")
+            }
+
+            fn visit_str<E: serde::de::Error>(self, name: &str) -> Result<Self::Value, E> {
+                ACTIONS
+                    .iter()
+                    .find(|(action, _)| *action == name)
+                    .map(Action)
+                    .ok_or_else(|| E::unknown_field(name, &ACTION_NAMES))
+            }
+        }
+
+        deserializer.deserialize_identifier(ActionVisitor)
+    }
+}
+
+/// The defaults of an action with its own fields, which takes the general fields directly too
+struct Defaults<F>(PhantomData<F>);
+
+impl<F: JsonSchema> JsonSchema for Defaults<F> {
+    fn schema_name() -> Cow<'static, str> {
+        format!("{}Defaults", F::schema_name().trim_end_matches("Opts")).into()
+    }
+
+    fn json_schema(generator: &mut SchemaGenerator) -> Schema {
+        crate::schema::merged::<F, OptsFields>(generator)
+    }
+}
+
+impl ActionDefaults {
+    /// Checks every action's defaults, general fields against `setup_opts` and the action's own
+    /// fields by their shape. For actions with their own fields, general fields like
+    /// `filter_rules` can be given directly and are moved under `opts`.
+    pub(crate) fn check(
+        self,
+        setup_opts: &Opts,
+        issues: &mut Issues,
+    ) -> Vec<(&'static str, Dictionary)> {
+        let general_names = validate::field_names::<OptsFields>();
+        let mut actions = Vec::new();
+        for (name, shape, object) in self.0 {
+            let path = format!("actions.{name}");
+            let kind = object.kind();
+            let Ok(dict) = obj_to_dict(object) else {
+                issues.push(path, format!("expected a table, got {}", kind.as_static()));
+                continue;
+            };
+            let dict = dict.unwrap_or_default();
+            match shape {
+                Shape::General => {
+                    let _ =
+                        Opts::parse_over(dict.clone().into(), setup_opts.clone(), issues, &path);
+                    actions.push((name, dict));
+                }
+                Shape::Nested(fields) => {
+                    let (general, own): (Dictionary, Dictionary) =
+                        dict.into_iter().partition(|(key, _)| {
+                            general_names.iter().any(|name| *key == name.as_str())
+                        });
+                    (fields.check)(own.clone().into(), issues, &path);
+                    let _ =
+                        Opts::parse_over(general.clone().into(), setup_opts.clone(), issues, &path);
+                    match nest_general_fields(own, general) {
+                        Ok(dict) => actions.push((name, dict)),
+                        Err(issue) => issues.push(format!("{path}.opts"), issue),
+                    }
+                }
+                Shape::Own(fields) => {
+                    (fields.check)(dict.clone().into(), issues, &path);
+                    actions.push((name, dict));
+                }
+            }
+        }
+        actions
+    }
+}
+
+/// Moves general fields under `opts`, merged over an `opts` table if one is given as well
+fn nest_general_fields(mut own: Dictionary, general: Dictionary) -> Result<Dictionary, String> {
+    if general.is_empty() {
+        return Ok(own);
+    }
+    let nested = match own.get_index("opts") {
+        Some(ind) => {
+            let opts = own.swap_remove(ind).into_value();
+            if opts.kind() == ObjectKind::String {
+                return Err("names a profile, general fields can't be set next to it".to_string());
+            }
+            // Anything but a table was reported when the fields were checked
+            let opts = obj_to_dict(opts).ok().flatten().unwrap_or_default();
+            merge_over(general, opts)
+        }
+        None => general,
+    };
+    own.insert("opts", nested);
+    Ok(own)
+}
+
+pub(crate) fn set_action_defaults(actions: Vec<(&'static str, Dictionary)>) {
+    ACTION_DEFAULTS.set(actions);
+}
+
+/// Merges the call-site `opts` over the defaults set up for `action`. Anything that isn't a
+/// table, like a profile name, is left alone for the parser
+pub(crate) fn with_action_defaults(action: &str, opts: Option<Object>) -> Option<Object> {
+    let defaults = ACTION_DEFAULTS.with_borrow(|actions| {
+        actions
+            .iter()
+            .find(|(name, _)| *name == action)
+            .map(|(_, defaults)| defaults.clone())
+    });
+    let Some(defaults) = defaults else {
+        return opts;
+    };
+    let over = match opts {
+        None => Dictionary::new(),
+        Some(opts) => match obj_to_dict(opts.clone()) {
+            Ok(over) => over.unwrap_or_default(),
+            Err(_) => return Some(opts),
+        },
+    };
+    Some(merge_over(defaults, over).into())
+}
+
+/// Tables are merged key by key, anything else in `over` replaces what's in `base`
+fn merge_over(mut base: Dictionary, over: Dictionary) -> Dictionary {
+    for (key, value) in over {
+        let Some(ind) = base.get_index(&key) else {
+            base.insert(key, value);
+            continue;
+        };
+        let slot = &mut base.as_mut_slice()[ind];
+        let merged = if slot.value().kind() == ObjectKind::Dictionary
+            && value.kind() == ObjectKind::Dictionary
+        {
+            let inner_base = Dictionary::from_object(slot.value().clone()).unwrap_or_default();
+            let inner_over = Dictionary::from_object(value).unwrap_or_default();
+            merge_over(inner_base, inner_over).into()
+        } else {
+            value
+        };
+        *slot.value_mut() = merged;
+    }
+    base
+}
@@ -1,6 +1,27 @@
-use anyhow::Context;
+use anyhow::{bail, Context};
 use nvim_oxi::api::Buffer;
 
+/// What to show in a newly opened window
+#[derive(Debug, Clone)]
+pub(crate) enum BufSource {
+    Path(String),
+    Buf(Buffer),
+}
+
+impl BufSource {
+    pub(crate) fn load(&self) -> anyhow::Result<Buffer> {
+        match self {
+            Self::Path(path) => load_file_to_hidden_buffer(path),
+            Self::Buf(buf) => {
+                if !buf.is_valid() {
+                    bail!("buffer {} is not valid", buf.handle());
+                }
+                Ok(buf.clone())
+            }
+        }
+    }
+}
+
 pub(crate) fn load_file_to_hidden_buffer(path: &str) -> anyhow::Result<Buffer> {
     let buf: Buffer = nvim_oxi::api::call_function("bufadd", (path,))
         .with_context(|| format!("failed to open buffer at {path}"))?;
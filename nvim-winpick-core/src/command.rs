@@ -0,0 +1,167 @@
+use anyhow::{bail, Context as _};
+use nvim_oxi::api::opts::CreateCommandOpts;
+use nvim_oxi::api::types::{
+    CommandArgs, CommandComplete, CommandModifiers, CommandNArgs, SplitDirection, SplitModifier,
+};
+use nvim_oxi::{Dictionary, Function};
+
+use crate::notify::notify_error;
+use crate::win::SplitKind;
+
+const COMMAND: &str = "Winpick";
+const SUBCOMMANDS: [&str; 6] = ["focus", "close", "swap", "split", "over", "relative"];
+/// Subcommands that take a path to open
+const OPENING: [&str; 3] = ["split", "over", "relative"];
+
+/// Registers `:Winpick <subcommand>`, replacing it if it already exists
+pub(crate) fn register() -> anyhow::Result<()> {
+    let complete = Function::from_fn(
+        |(arg_lead, cmd_line, cursor_pos): (String, String, usize)| {
+            complete(&arg_lead, &cmd_line, cursor_pos)
+        },
+    );
+    let opts = CreateCommandOpts::builder()
+        .nargs(CommandNArgs::OneOrMore)
+        .bang(true)
+        .complete(CommandComplete::CustomList(complete))
+        .desc("Pick a window to focus, close, swap with, or open a file at")
+        .force(true)
+        .build();
+    nvim_oxi::api::create_user_command(
+        COMMAND,
+        |args: CommandArgs| {
+            if let Err(e) = run(&args) {
+                notify_error(&format!("[nvim_winpick] :{COMMAND} failed: {e:#?}"));
+            }
+        },
+        &opts,
+    )
+    .context("failed to create user command")
+}
+
+fn run(args: &CommandArgs) -> anyhow::Result<()> {
+    let raw = args.args.as_deref().unwrap_or_default().trim();
+    let (sub, rest) = raw.split_once(char::is_whitespace).unwrap_or((raw, ""));
+    let rest = rest.trim();
+    if OPENING.contains(&sub) {
+        if rest.is_empty() {
+            bail!("'{sub}' needs a path to open");
+        }
+    } else if !rest.is_empty() {
+        bail!("'{sub}' doesn't take arguments, got '{rest}'");
+    }
+    if args.bang && sub != "close" {
+        bail!("'!' is only supported for 'close', to discard unsaved changes");
+    }
+    if (args.smods.vertical || args.smods.split.is_some()) && sub != "split" {
+        bail!("split modifiers are only supported for 'split', to pick the side it opens on");
+    }
+    let unsupported = unsupported_modifiers(&args.smods);
+    if !unsupported.is_empty() {
+        bail!(
+            "{} not supported, the picked window decides where things open",
+            unsupported.join(", ")
+        );
+    }
+    match sub {
+        "focus" => crate::pick_focus_window(None),
+        "close" => {
+            let mut close_opts = Dictionary::new();
+            if args.bang {
+                close_opts.insert("modified", "discard");
+            }
+            // The summary is for callers from lua, skipped windows are already reported
+            let _ = crate::pick_close_window(Some(close_opts.into()));
+        }
+        "swap" => crate::pick_swap_window(None),
+        "split" => {
+            let mut split_opts = Dictionary::new();
+            split_opts.insert("path", expand(rest)?);
+            // The modifiers override which side of the picked window it's on
+            crate::open_split_as(Some(split_opts.into()), |vertical| {
+                split_kind(vertical || args.smods.vertical, args.smods.split)
+            });
+        }
+        "over" | "relative" => {
+            let mut open_opts = Dictionary::new();
+            open_opts.insert("path", expand(rest)?);
+            if sub == "over" {
+                crate::open_over(Some(open_opts.into()));
+            } else {
+                crate::pick_win_relative(Some(open_opts.into()));
+            }
+        }
+        unk => bail!(
+            "unknown subcommand '{unk}', expected one of {}",
+            SUBCOMMANDS.join(", ")
+        ),
+    }
+    Ok(())
+}
+
+/// Modifiers that change where or how windows and buffers open, which the actions do
+/// themselves. Neovim keeps the others, like `:silent` and `:noautocmd`, in effect while the
+/// command runs
+fn unsupported_modifiers(smods: &CommandModifiers) -> Vec<&'static str> {
+    [
+        (smods.tab >= 0, ":tab"),
+        (smods.hide, ":hide"),
+        (smods.browse, ":browse"),
+        (smods.confirm, ":confirm"),
+    ]
+    .into_iter()
+    .filter_map(|(set, name)| set.then_some(name))
+    .collect()
+}
+
+/// `:vertical` splits left or right instead of above or below, `:aboveleft` and `:topleft`
+/// pick the first side of the picked window, `:belowright` and `:botright` the second
+fn split_kind(vertical: bool, modifier: Option<SplitModifier>) -> SplitKind {
+    let first = match modifier {
+        None => return SplitKind::Default { vertical },
+        Some(SplitModifier::AboveLeft | SplitModifier::TopLeft) => true,
+        Some(_) => false,
+    };
+    let direction = match (vertical, first) {
+        (true, true) => SplitDirection::Left,
+        (true, false) => SplitDirection::Right,
+        (false, true) => SplitDirection::Above,
+        (false, false) => SplitDirection::Below,
+    };
+    SplitKind::Side(direction)
+}
+
+/// Expands `%`, `~` and environment variables the way `:edit` does
+fn expand(path: &str) -> anyhow::Result<String> {
+    nvim_oxi::api::call_function("expandcmd", (path,))
+        .with_context(|| format!("failed to expand '{path}'"))
+}
+
+/// Subcommands for the first argument, file paths for the argument of opening subcommands
+fn complete(arg_lead: &str, cmd_line: &str, cursor_pos: usize) -> Vec<String> {
+    let before = cmd_line.get(..cursor_pos).unwrap_or(cmd_line);
+    let words: Vec<&str> = before.split_whitespace().collect();
+    // Modifiers like `:vert` come before the command, which can be abbreviated
+    let Some(cmd_at) = words.iter().position(|word| {
+        let word = word.trim_end_matches('!');
+        word.starts_with('W') && COMMAND.starts_with(word)
+    }) else {
+        return Vec::new();
+    };
+    let mut args = &words[cmd_at + 1..];
+    // The word being completed isn't finished yet
+    if !arg_lead.is_empty() {
+        args = &args[..args.len().saturating_sub(1)];
+    }
+    match args {
+        [] => SUBCOMMANDS
+            .iter()
+            .filter(|sub| sub.starts_with(arg_lead))
+            .map(ToString::to_string)
+            .collect(),
+        [sub, ..] if OPENING.contains(sub) => {
+            nvim_oxi::api::call_function("getcompletion", (arg_lead, "file")).unwrap_or_default()
+        }
+        _ => Vec::new(),
+    }
+}
@@ -1,12 +1,36 @@
+use std::cell::Cell;
+
 use nvim_oxi::api::Window;
 
+use crate::opts::Opts;
+
+thread_local! {
+    /// The count a `<Plug>` mapping was invoked with, only set while its action runs
+    static ORDINAL: Cell<Option<usize>> = const { Cell::new(None) };
+}
+
 /// Simple context where immutable things that may be requested a lot can be cached
 pub(crate) struct Context {
     current_win: Option<Window>,
+    ordinal: Option<usize>,
 }
 
 impl Context {
-    pub(crate) const DEFAULT: Self = Self { current_win: None };
+    /// Without a count, for picks that are never resolved by one
+    pub(crate) const DEFAULT: Self = Self {
+        current_win: None,
+        ordinal: None,
+    };
+
+    /// For an action run with `opts`, its first pick is resolved by their `count`, or else by
+    /// the count of the `<Plug>` mapping running it
+    pub(crate) fn new(opts: &Opts) -> Self {
+        Self {
+            current_win: None,
+            ordinal: opts.count.or_else(|| ORDINAL.get()),
+        }
+    }
+
     pub(crate) fn get_current_win(&mut self) -> Window {
         if let Some(win) = self.current_win.clone() {
             return win;
@@ -15,4 +39,27 @@ impl Context {
         self.current_win = Some(current_win.clone());
         current_win
     }
+
+    /// The count the action was run with, if any. Only the first pick of an action uses it to
+    /// take the window with that (1-based) label position instead of drawing, later picks, like
+    /// the window in the destination tab, are drawn as usual.
+    /// `v:count` is only read for `<Plug>` mappings, it keeps the count of an earlier keypress
+    /// when the action is called from lua, an autocmd or a command, those pass `count` instead
+    pub(crate) fn take_ordinal(&mut self) -> Option<usize> {
+        self.ordinal.take()
+    }
+}
+
+/// Runs `action` with its first pick resolved by `v:count`, see [`Context::take_ordinal`]
+pub(crate) fn with_count<R>(action: impl FnOnce() -> R) -> R {
+    let count: usize = nvim_oxi::api::get_vvar("count").unwrap_or_default();
+    with_ordinal((count > 0).then_some(count), action)
+}
+
+/// Runs `action` with its first pick resolved by `ordinal`, see [`Context::take_ordinal`]
+pub(crate) fn with_ordinal<R>(ordinal: Option<usize>, action: impl FnOnce() -> R) -> R {
+    ORDINAL.set(ordinal);
+    let res = action();
+    ORDINAL.set(None);
+    res
 }
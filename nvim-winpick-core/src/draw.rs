@@ -1,21 +1,31 @@
+use std::fmt::Write;
+
 use anyhow::{Context, Result};
 use nvim_oxi::api::{
-    opts::BufDeleteOptsBuilder,
+    opts::{BufDeleteOptsBuilder, OptionOpts},
     types::{WindowBorder, WindowBorderChar, WindowConfig, WindowStyle},
-    Window,
+    TabPage, Window,
 };
 
-use crate::geometry::{get_overlay_centered_position, Positions, WindowDimensions};
+use crate::geometry::{
+    get_overlay_centered_position, Direction, Positions, Region, WindowDimensions,
+};
+use crate::opts::RelativeKeys;
+use crate::win::{editor_dimensions, SplitSize};
 
-pub(crate) struct Drawer<'a, S> {
-    chars: &'a str,
+pub(crate) struct Drawer<S> {
     draw_setting: S,
     drawn_windows: Vec<Window>,
 }
 
-impl Drawer<'_, PickBetweenWindows<'_>> {
+impl Drawer<PickBetweenWindows<'_>> {
     pub(crate) fn target_win_for_char(&self, user: char) -> Option<Window> {
-        for (ch, win) in self.chars.chars().zip(self.draw_setting.0.iter()) {
+        for (ch, win) in self
+            .draw_setting
+            .chars
+            .chars()
+            .zip(self.draw_setting.windows.iter())
+        {
             if ch.eq_ignore_ascii_case(&user) {
                 return Some(win.clone());
             }
@@ -24,10 +34,11 @@ impl Drawer<'_, PickBetweenWindows<'_>> {
     }
     pub(crate) fn target_and_drawn_win_for_char(&self, user: char) -> Option<(Window, Window)> {
         for ((ch, drawn), target) in self
+            .draw_setting
             .chars
             .chars()
             .zip(&self.drawn_windows)
-            .zip(self.draw_setting.0.iter())
+            .zip(self.draw_setting.windows.iter())
         {
             if ch.eq_ignore_ascii_case(&user) {
                 return Some((target.clone(), drawn.clone()));
@@ -37,32 +48,38 @@ impl Drawer<'_, PickBetweenWindows<'_>> {
     }
 }
 
-impl<S> Drawer<'_, S> {
+impl<S> Drawer<S> {
     pub(crate) fn clear(&mut self) -> Result<()> {
         for win in self.drawn_windows.drain(..) {
-            if win.is_valid() {
-                let buf = win
-                    .get_buf()
-                    .context("failed to clean window, failed to get buf")?;
-                win.close(true).context("failed to close window")?;
-                let mut bdel = BufDeleteOptsBuilder::default();
-                bdel.force(true);
-                buf.delete(&bdel.build())
-                    .context("failed to delete buffer")?;
-            }
+            close_drawn(win)?;
         }
         Ok(())
     }
 }
 
-impl<S> Drop for Drawer<'_, S> {
+/// Closes a window drawn by this plugin and deletes its scratch buffer
+fn close_drawn(win: Window) -> Result<()> {
+    if win.is_valid() {
+        let buf = win
+            .get_buf()
+            .context("failed to clean window, failed to get buf")?;
+        win.close(true).context("failed to close window")?;
+        let mut bdel = BufDeleteOptsBuilder::default();
+        bdel.force(true);
+        buf.delete(&bdel.build())
+            .context("failed to delete buffer")?;
+    }
+    Ok(())
+}
+
+impl<S> Drop for Drawer<S> {
     fn drop(&mut self) {
         let _ = self.clear();
     }
 }
 
-pub(crate) struct FloatingBigLetterDrawer<'a, S> {
-    inner: Drawer<'a, S>,
+pub(crate) struct FloatingBigLetterDrawer<S> {
+    inner: Drawer<S>,
 }
 
 pub(crate) trait PickBetweenWindowsDrawer {
@@ -71,12 +88,11 @@ pub(crate) trait PickBetweenWindowsDrawer {
     fn target_and_drawn_win_for_char(&self, user: char) -> Option<(Window, Window)>;
 }
 
-impl<'a> FloatingBigLetterDrawer<'a, PickBetweenWindows<'a>> {
+impl<'a> FloatingBigLetterDrawer<PickBetweenWindows<'a>> {
     pub(crate) fn new(chars: &'a str, windows: &'a [Window]) -> Self {
         Self {
             inner: Drawer {
-                chars,
-                draw_setting: PickBetweenWindows(windows),
+                draw_setting: PickBetweenWindows { chars, windows },
                 drawn_windows: Vec::new(),
             },
         }
@@ -122,15 +138,16 @@ impl<'a> FloatingBigLetterDrawer<'a, PickBetweenWindows<'a>> {
     }
 }
 
-impl<'a> PickBetweenWindowsDrawer for FloatingBigLetterDrawer<'a, PickBetweenWindows<'a>> {
+impl PickBetweenWindowsDrawer for FloatingBigLetterDrawer<PickBetweenWindows<'_>> {
     fn draw(&mut self) -> Result<()> {
-        for (win, ch) in self
-            .inner
-            .draw_setting
-            .0
+        let setting = &self.inner.draw_setting;
+        let targets: Vec<(Window, char)> = setting
+            .windows
             .iter()
-            .zip(self.inner.chars.chars())
-        {
+            .cloned()
+            .zip(setting.chars.chars())
+            .collect();
+        for (win, ch) in targets {
             self.show_letter_in_window(win.clone(), ch)?;
         }
         Ok(())
@@ -147,20 +164,27 @@ impl<'a> PickBetweenWindowsDrawer for FloatingBigLetterDrawer<'a, PickBetweenWin
     }
 }
 
-pub(crate) struct PickBetweenWindowSplits<'a>(&'a Window);
+pub(crate) struct PickBetweenWindowSplits<'a> {
+    keys: &'a RelativeKeys,
+    window: &'a Window,
+    /// The ghost of the hovered position's region, also in `drawn_windows`
+    preview: Option<Window>,
+}
 
-pub(crate) struct PickBetweenWindows<'a>(&'a [Window]);
+pub(crate) struct PickBetweenWindows<'a> {
+    chars: &'a str,
+    windows: &'a [Window],
+}
 
-pub(crate) struct FloatingLetterDrawer<'a, S> {
-    inner: Drawer<'a, S>,
+pub(crate) struct FloatingLetterDrawer<S> {
+    inner: Drawer<S>,
 }
 
-impl<'a> FloatingLetterDrawer<'a, PickBetweenWindows<'a>> {
+impl<'a> FloatingLetterDrawer<PickBetweenWindows<'a>> {
     pub(crate) fn new_pick_between(chars: &'a str, windows: &'a [Window]) -> Self {
         Self {
             inner: Drawer {
-                chars,
-                draw_setting: PickBetweenWindows(windows),
+                draw_setting: PickBetweenWindows { chars, windows },
                 drawn_windows: Vec::new(),
             },
         }
@@ -196,15 +220,16 @@ impl<'a> FloatingLetterDrawer<'a, PickBetweenWindows<'a>> {
     }
 }
 
-impl<'a> PickBetweenWindowsDrawer for FloatingLetterDrawer<'a, PickBetweenWindows<'a>> {
+impl PickBetweenWindowsDrawer for FloatingLetterDrawer<PickBetweenWindows<'_>> {
     fn draw(&mut self) -> Result<()> {
-        for (win, ch) in self
-            .inner
-            .draw_setting
-            .0
+        let setting = &self.inner.draw_setting;
+        let targets: Vec<(Window, char)> = setting
+            .windows
             .iter()
-            .zip(self.inner.chars.chars())
-        {
+            .cloned()
+            .zip(setting.chars.chars())
+            .collect();
+        for (win, ch) in targets {
             self.show_letter_in_window(win.clone(), ch)?;
         }
         Ok(())
@@ -220,12 +245,15 @@ impl<'a> PickBetweenWindowsDrawer for FloatingLetterDrawer<'a, PickBetweenWindow
     }
 }
 
-impl<'a> FloatingLetterDrawer<'a, PickBetweenWindowSplits<'a>> {
-    pub(crate) fn new_draw_within(chars: &'a str, window: &'a Window) -> Self {
+impl<'a> FloatingLetterDrawer<PickBetweenWindowSplits<'a>> {
+    pub(crate) fn new_draw_within(keys: &'a RelativeKeys, window: &'a Window) -> Self {
         Self {
             inner: Drawer {
-                chars,
-                draw_setting: PickBetweenWindowSplits(window),
+                draw_setting: PickBetweenWindowSplits {
+                    keys,
+                    window,
+                    preview: None,
+                },
                 drawn_windows: Vec::new(),
             },
         }
@@ -237,11 +265,13 @@ impl<'a> FloatingLetterDrawer<'a, PickBetweenWindowSplits<'a>> {
             width: 3,
             height: 1,
         };
-        let positions =
-            crate::geometry::Positions::calculate_indicator_positions(win_dims, overlay_dims)?;
-        // Ordering of this array is implicitly important, really dumb implementation by me
-        let char_iter = self.inner.chars.chars().zip(positions);
-        for (ch, pos) in char_iter {
+        let keys = self.inner.draw_setting.keys;
+        let points = Positions::indicator_positions(
+            keys.iter().map(|(position, _)| position),
+            win_dims,
+            overlay_dims,
+        )?;
+        for ((_, ch), (_, pos)) in keys.iter().zip(points) {
             let line = format!(" {ch} ");
             let width: u32 = 3;
             let height: u32 = 1;
@@ -271,17 +301,203 @@ impl<'a> FloatingLetterDrawer<'a, PickBetweenWindowSplits<'a>> {
     }
 
     pub(crate) fn draw_multi(&mut self) -> Result<()> {
-        self.show_multi_letter_in_window(self.inner.draw_setting.0)?;
+        self.show_multi_letter_in_window(self.inner.draw_setting.window)?;
         Ok(())
     }
 
     pub(crate) fn pos_for_char(&self, user: char) -> Option<Positions> {
-        for (ch, pos) in self.inner.chars.chars().zip(Positions::iter()) {
+        self.inner.draw_setting.keys.position_for_char(user)
+    }
+
+    /// Draws a ghost of the region a new window at `position` would cover, with splits taking
+    /// `size`, replacing the previously drawn ghost
+    pub(crate) fn draw_preview(
+        &mut self,
+        position: Positions,
+        size: Option<SplitSize>,
+    ) -> Result<()> {
+        if let Some(old) = self.inner.draw_setting.preview.take() {
+            self.inner.drawn_windows.retain(|win| win != &old);
+            close_drawn(old)?;
+        }
+        let window = self.inner.draw_setting.window;
+        let (row, col) = window
+            .get_position()
+            .context("failed to get window position")?;
+        let region = Region {
+            row: u32::try_from(row).context("window row out of range")?,
+            col: u32::try_from(col).context("window col out of range")?,
+            width: window.get_width().context("failed to get window width")?,
+            height: window.get_height().context("failed to get window height")?,
+        };
+        let ghost = position.preview_region(region, editor_dimensions()?, size);
+        nvim_oxi::api::command(&format!("highlight default link {PREVIEW_HL_GROUP} Visual"))
+            .context("failed to set preview highlight")?;
+        let buffer =
+            nvim_oxi::api::create_buf(false, true).context("failed to create preview buffer")?;
+        let mut wc = WindowConfig::default();
+        wc.relative = Some(nvim_oxi::api::types::WindowRelativeTo::Editor);
+        wc.focusable = Some(false);
+        // Leaves room for the border
+        wc.width = Some(ghost.width.saturating_sub(2).max(1));
+        wc.height = Some(ghost.height.saturating_sub(2).max(1));
+        wc.row = Some(f64::from(ghost.row));
+        wc.col = Some(f64::from(ghost.col));
+        wc.style = Some(WindowStyle::Minimal);
+        wc.border = Some(border());
+        wc.zindex = Some(PREVIEW_ZINDEX);
+        let rendered = nvim_oxi::api::open_win(&buffer, false, &wc)
+            .context("failed to open preview window")?;
+        self.inner.drawn_windows.push(rendered.clone());
+        let opts = OptionOpts::builder().win(rendered.clone()).build();
+        nvim_oxi::api::set_option_value("winblend", PREVIEW_BLEND, &opts)
+            .context("failed to set preview blend")?;
+        nvim_oxi::api::set_option_value(
+            "winhighlight",
+            format!("Normal:{PREVIEW_HL_GROUP}"),
+            &opts,
+        )
+        .context("failed to set preview highlight")?;
+        self.inner.draw_setting.preview = Some(rendered);
+        Ok(())
+    }
+
+    /// The offered position closest to `from` in `direction`, going by where their keys are drawn
+    pub(crate) fn neighbour(
+        &self,
+        from: Positions,
+        direction: Direction,
+    ) -> Result<Option<Positions>> {
+        let window = self.inner.draw_setting.window;
+        let win_config = window.get_config().context("failed to get window config")?;
+        let win_dims = WindowDimensions::try_from_win_cfg(&win_config)?;
+        let overlay_dims = WindowDimensions {
+            width: 3,
+            height: 1,
+        };
+        let points = Positions::indicator_positions(
+            self.inner
+                .draw_setting
+                .keys
+                .iter()
+                .map(|(position, _)| position),
+            win_dims,
+            overlay_dims,
+        )?;
+        let Some((_, origin)) = points.iter().find(|(position, _)| *position == from) else {
+            return Ok(None);
+        };
+        let mut closest: Option<(f64, Positions)> = None;
+        for (position, point) in &points {
+            let position = *position;
+            if position == from {
+                continue;
+            }
+            let Some((along, across)) = direction.distance(origin, point) else {
+                continue;
+            };
+            // Straying from the direction counts double, so moving right prefers the same row
+            let score = along + 2.0 * across;
+            if closest.is_none_or(|(best, _)| score < best) {
+                closest = Some((score, position));
+            }
+        }
+        Ok(closest.map(|(_, position)| position))
+    }
+}
+
+pub(crate) enum TabTarget {
+    Existing(TabPage),
+    New,
+}
+
+/// Draws labels for tabpages by temporarily replacing the tabline.
+/// One label is drawn per tab, followed by a label for opening a new tab if there are chars left.
+/// The old tabline is restored when cleared or dropped.
+pub(crate) struct TablineDrawer<'a> {
+    chars: &'a str,
+    tabs: &'a [TabPage],
+    restore: Option<(String, i64)>,
+}
+
+impl<'a> TablineDrawer<'a> {
+    pub(crate) fn new(chars: &'a str, tabs: &'a [TabPage]) -> Self {
+        Self {
+            chars,
+            tabs,
+            restore: None,
+        }
+    }
+
+    pub(crate) fn draw(&mut self) -> Result<()> {
+        let opts = OptionOpts::default();
+        let old_tabline: String = nvim_oxi::api::get_option_value("tabline", &opts)
+            .context("failed to get current tabline")?;
+        let old_showtabline: i64 = nvim_oxi::api::get_option_value("showtabline", &opts)
+            .context("failed to get current showtabline")?;
+        self.restore = Some((old_tabline, old_showtabline));
+        let mut tabline = String::new();
+        let mut chars = self.chars.chars();
+        for (tab, ch) in self.tabs.iter().zip(chars.by_ref()) {
+            let num = tab.get_number().context("failed to get tab number")?;
+            let _ = write!(
+                tabline,
+                "%#TabLineSel# {} %#TabLine# tab {num} ",
+                escape_statusline(ch)
+            );
+        }
+        if let Some(ch) = chars.next() {
+            let _ = write!(
+                tabline,
+                "%#TabLineSel# {} %#TabLine# new tab ",
+                escape_statusline(ch)
+            );
+        }
+        tabline.push_str("%#TabLineFill#");
+        nvim_oxi::api::set_option_value("showtabline", 2, &opts)
+            .context("failed to show tabline")?;
+        nvim_oxi::api::set_option_value("tabline", tabline, &opts)
+            .context("failed to set tabline")?;
+        Ok(())
+    }
+
+    pub(crate) fn target_for_char(&self, user: char) -> Option<TabTarget> {
+        let mut chars = self.chars.chars();
+        // Tabs first, otherwise zip consumes the new tab char when the tabs run out
+        for (tab, ch) in self.tabs.iter().zip(chars.by_ref()) {
             if ch.eq_ignore_ascii_case(&user) {
-                return Some(pos);
+                return Some(TabTarget::Existing(tab.clone()));
             }
         }
-        None
+        chars
+            .next()
+            .filter(|ch| ch.eq_ignore_ascii_case(&user))
+            .map(|_| TabTarget::New)
+    }
+
+    pub(crate) fn clear(&mut self) -> Result<()> {
+        if let Some((tabline, showtabline)) = self.restore.take() {
+            let opts = OptionOpts::default();
+            nvim_oxi::api::set_option_value("tabline", tabline, &opts)
+                .context("failed to restore tabline")?;
+            nvim_oxi::api::set_option_value("showtabline", showtabline, &opts)
+                .context("failed to restore showtabline")?;
+        }
+        Ok(())
+    }
+}
+
+impl Drop for TablineDrawer<'_> {
+    fn drop(&mut self) {
+        let _ = self.clear();
+    }
+}
+
+fn escape_statusline(ch: char) -> String {
+    if ch == '%' {
+        "%%".to_string()
+    } else {
+        ch.to_string()
     }
 }
 
@@ -289,6 +505,10 @@ pub(crate) const BIG_CHAR_WIDTH: u32 = 18;
 pub(crate) const BIG_CHAR_HEIGHT: u32 = 8;
 
 const BORDER_HL_GROUP: &str = "FloatBoarder";
+const PREVIEW_HL_GROUP: &str = "WinpickPreview";
+/// Below the default of 50, so the keys are drawn on top of the preview
+const PREVIEW_ZINDEX: u32 = 40;
+const PREVIEW_BLEND: u32 = 60;
 
 fn border() -> WindowBorder {
     WindowBorder::Anal(
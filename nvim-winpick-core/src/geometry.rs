@@ -1,7 +1,11 @@
 use std::{f64, ops::Sub};
 
 use anyhow::{bail, Context, Result};
-use nvim_oxi::api::types::WindowConfig;
+use nvim_oxi::api::types::{SplitDirection, WindowConfig};
+use schemars::JsonSchema;
+use serde::Deserialize;
+
+use crate::win::SplitSize;
 
 #[derive(Clone, Copy)]
 pub(crate) struct WindowDimensions {
@@ -17,22 +21,48 @@ impl WindowDimensions {
     }
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Deserialize, JsonSchema)]
+#[schemars(rename = "Position")]
 pub(crate) enum Positions {
+    #[serde(rename = "full_top")]
     TopFullHor,
+    #[serde(rename = "full_right")]
     RightFullVer,
+    #[serde(rename = "full_bottom")]
     BotFullHor,
+    #[serde(rename = "full_left")]
     LeftFullVer,
+    #[serde(rename = "split_top")]
     SplitTop,
+    #[serde(rename = "split_right")]
     SplitRight,
+    #[serde(rename = "split_bottom")]
     SplitBot,
+    #[serde(rename = "split_left")]
     SplitLeft,
+    #[serde(rename = "center")]
     Center,
+    #[serde(rename = "corner_top_left")]
+    CornerTopLeft,
+    #[serde(rename = "corner_top_right")]
+    CornerTopRight,
+    #[serde(rename = "corner_bottom_left")]
+    CornerBotLeft,
+    #[serde(rename = "corner_bottom_right")]
+    CornerBotRight,
+    #[serde(rename = "float")]
+    Float,
+    #[serde(rename = "new_tab")]
+    NewTab,
+    #[serde(rename = "replace_keep_alternate")]
+    ReplaceKeepAlternate,
 }
 
 impl Positions {
-    const NUM_POSITIONS: usize = 9;
-    pub(crate) fn iter() -> [Self; Self::NUM_POSITIONS] {
+    const NUM_DEFAULTS: usize = 9;
+    /// The positions offered when neither `relative_keys` nor `relative_positions` choose them,
+    /// in the order that chars are assigned to them. The others are opt-in
+    pub(crate) fn defaults() -> [Self; Self::NUM_DEFAULTS] {
         [
             Self::TopFullHor,
             Self::RightFullVer,
@@ -46,10 +76,52 @@ impl Positions {
         ]
     }
 
-    pub(crate) fn calculate_indicator_positions(
+    /// Where to draw the indicators for `positions` within a window, in the same order.
+    /// Indicators that would cover an earlier one, like `Float` and `TopFullHor` which both end
+    /// up on the top edge of a short window, are moved sideways until they're clear of it
+    pub(crate) fn indicator_positions(
+        positions: impl Iterator<Item = Self>,
+        win: WindowDimensions,
+        overlay: WindowDimensions,
+    ) -> anyhow::Result<Vec<(Self, ScreenPoint)>> {
+        // Overlays are drawn with a border
+        let box_width = f64::from(overlay.width) + 2.0;
+        let box_height = f64::from(overlay.height) + 2.0;
+        let max_x = (f64::from(win.width) - box_width).max(0.0);
+        let overlaps = |point: &ScreenPoint, placed: &[(Self, ScreenPoint)]| {
+            placed.iter().any(|(_, other)| {
+                (point.x - other.x).abs() < box_width && (point.y - other.y).abs() < box_height
+            })
+        };
+        let mut placed: Vec<(Self, ScreenPoint)> = Vec::new();
+        for position in positions {
+            let mut point = position.indicator_position(win, overlay)?;
+            if overlaps(&point, &placed) {
+                let start = point.x;
+                let y = point.y;
+                // Left as is if the window is too narrow to fit it anywhere else
+                if let Some(x) = (1..=win.width)
+                    .map(|step| f64::from(step) * box_width)
+                    .take_while(|offset| start + offset <= max_x || start - offset >= 0.0)
+                    .flat_map(|offset| [start + offset, start - offset])
+                    .filter(|x| (0.0..=max_x).contains(x))
+                    .find(|&x| !overlaps(&ScreenPoint { x, y }, &placed))
+                {
+                    point.x = x;
+                }
+            }
+            placed.push((position, point));
+        }
+        Ok(placed)
+    }
+
+    /// Where to draw the indicator for this position within a window, without regard for the
+    /// other indicators, see [`Self::indicator_positions`]
+    fn indicator_position(
+        self,
         win: WindowDimensions,
         overlay: WindowDimensions,
-    ) -> anyhow::Result<[ScreenPoint; Self::NUM_POSITIONS]> {
+    ) -> anyhow::Result<ScreenPoint> {
         let win_center_x: f64 = f64::from(win.width) / 2.0;
         let overlay_center_x: f64 = f64::from(overlay.width) / 2.0;
         let center_left_x = win_center_x.sub(overlay_center_x);
@@ -59,71 +131,180 @@ impl Positions {
         let win_height = f64::from(win.height);
         let win_width = f64::from(win.width);
         let overlay_height = f64::from(overlay.height);
-        let top_full_hor = ScreenPoint {
-            x: center_left_x,
-            // I don't know why this is correct but it is, half an overlay's worth of padding from top
-            // edge
-            y: 0.0,
-        };
-        let bot_full_hor = ScreenPoint {
-            x: center_left_x,
-            // Don't know why this is correct either, but it is, half an overlay's worth of padding
-            // from the bottom edge
-            y: win_height - 3.0 * overlay_height,
-        };
         let win_center_y = f64::from(win.height) / 2.0;
         let overlay_center_y = f64::from(overlay.height) / 2.0;
         let center_overlay_y = win_center_y - overlay_height;
-        let center = ScreenPoint {
-            x: center_left_x,
-            y: center_overlay_y,
-        };
         let win_vert_split_length = f64::from(win.height) / 4.0;
         let win_split_top_center_y = win_vert_split_length - overlay_center_y;
         let win_split_bot_center_y = 3.0 * (win_vert_split_length - overlay_center_y);
-        let split_top = ScreenPoint {
-            x: center_left_x,
-            y: win_split_top_center_y,
-        };
-        let split_bot = ScreenPoint {
-            x: center_left_x,
-            y: win_split_bot_center_y,
-        };
-
         let win_hor_split_length = f64::from(win.width) / 4.0;
         let win_split_left_center_x = win_hor_split_length - overlay_center_x;
         let win_split_right_center_x = 3.0 * win_hor_split_length - overlay_center_x;
-        let split_right = ScreenPoint {
-            x: win_split_right_center_x,
-            y: center_overlay_y,
-        };
-        let split_left = ScreenPoint {
-            x: win_split_left_center_x,
-            y: center_overlay_y,
+        let right_edge_x = win_width - 4.0 * overlay_center_x;
+        let (x, y) = match self {
+            // I don't know why this is correct but it is, half an overlay's worth of padding from top
+            // edge
+            Self::TopFullHor => (center_left_x, 0.0),
+            // Don't know why this is correct either, but it is, half an overlay's worth of padding
+            // from the bottom edge
+            Self::BotFullHor => (center_left_x, win_height - 3.0 * overlay_height),
+            Self::Center => (center_left_x, center_overlay_y),
+            Self::SplitTop => (center_left_x, win_split_top_center_y),
+            Self::SplitBot => (center_left_x, win_split_bot_center_y),
+            Self::SplitRight => (win_split_right_center_x, center_overlay_y),
+            Self::SplitLeft => (win_split_left_center_x, center_overlay_y),
+            Self::LeftFullVer => (0.0, center_overlay_y),
+            Self::RightFullVer => (right_edge_x, center_overlay_y),
+            Self::CornerTopLeft => (win_split_left_center_x, win_split_top_center_y),
+            Self::CornerTopRight => (win_split_right_center_x, win_split_top_center_y),
+            Self::CornerBotLeft => (win_split_left_center_x, win_split_bot_center_y),
+            Self::CornerBotRight => (win_split_right_center_x, win_split_bot_center_y),
+            // One bordered overlay above and below the center respectively
+            Self::Float => (center_left_x, center_overlay_y - 3.0 * overlay_height),
+            Self::ReplaceKeepAlternate => (center_left_x, center_overlay_y + 3.0 * overlay_height),
+            Self::NewTab => (right_edge_x, 0.0),
         };
-        let left_full_ver = ScreenPoint {
-            x: 0.0,
-            y: center_overlay_y,
+        Ok(ScreenPoint { x, y: y.max(0.0) })
+    }
+
+    /// The region, in editor cells, a new window at this position would roughly cover, given
+    /// the target window's `region` and the `editor` size. New splits take `size` of the space
+    /// they're split from, or half of it like Vim does by default, corners always take half
+    pub(crate) fn preview_region(
+        self,
+        region: Region,
+        editor: WindowDimensions,
+        size: Option<SplitSize>,
+    ) -> Region {
+        let Region {
+            row,
+            col,
+            width,
+            height,
+        } = region;
+        let split = |available: u32| {
+            size.map_or((available / 2).max(1), |size| {
+                size.resolve(available).min(available)
+            })
         };
-        let right_full_ver = ScreenPoint {
-            x: win_width - 4.0 * overlay_center_x,
-            y: center_overlay_y,
+        let half_w = (width / 2).max(1);
+        let half_h = (height / 2).max(1);
+        let (split_w, split_h) = (split(width), split(height));
+        let (editor_w, editor_h) = (split(editor.width), split(editor.height));
+        let (row, col, width, height) = match self {
+            Self::TopFullHor => (0, 0, editor.width, editor_h),
+            Self::BotFullHor => (
+                editor.height.saturating_sub(editor_h),
+                0,
+                editor.width,
+                editor_h,
+            ),
+            Self::LeftFullVer => (0, 0, editor_w, editor.height),
+            Self::RightFullVer => (
+                0,
+                editor.width.saturating_sub(editor_w),
+                editor_w,
+                editor.height,
+            ),
+            Self::SplitTop => (row, col, width, split_h),
+            Self::SplitBot => (row + height.saturating_sub(split_h), col, width, split_h),
+            Self::SplitLeft => (row, col, split_w, height),
+            Self::SplitRight => (row, col + width.saturating_sub(split_w), split_w, height),
+            Self::CornerTopLeft => (row, col, half_w, half_h),
+            Self::CornerTopRight => (row, col + width.saturating_sub(half_w), half_w, half_h),
+            Self::CornerBotLeft => (row + height.saturating_sub(half_h), col, half_w, half_h),
+            Self::CornerBotRight => (
+                row + height.saturating_sub(half_h),
+                col + width.saturating_sub(half_w),
+                half_w,
+                half_h,
+            ),
+            Self::Center | Self::ReplaceKeepAlternate | Self::Float => (row, col, width, height),
+            Self::NewTab => (0, 0, editor.width, editor.height),
         };
+        Region {
+            row,
+            col,
+            width,
+            height,
+        }
+    }
+
+    /// The dimension a new split at this position takes its size from, `None` for positions
+    /// that don't split, or split twice
+    pub(crate) fn split_axis(self) -> Option<SplitAxis> {
+        match self {
+            Self::TopFullHor | Self::BotFullHor | Self::SplitTop | Self::SplitBot => {
+                Some(SplitAxis::Rows)
+            }
+            Self::RightFullVer | Self::LeftFullVer | Self::SplitRight | Self::SplitLeft => {
+                Some(SplitAxis::Cols)
+            }
+            _ => None,
+        }
+    }
+
+    /// Whether the position splits the whole tabpage rather than a window
+    pub(crate) fn is_full(self) -> bool {
+        matches!(
+            self,
+            Self::TopFullHor | Self::RightFullVer | Self::BotFullHor | Self::LeftFullVer
+        )
+    }
+
+    /// The directions of the two splits that make up a corner, the first splits the target
+    /// window in half, the second splits that half
+    pub(crate) fn corner_splits(self) -> Option<(SplitDirection, SplitDirection)> {
+        match self {
+            Self::CornerTopLeft => Some((SplitDirection::Above, SplitDirection::Left)),
+            Self::CornerTopRight => Some((SplitDirection::Above, SplitDirection::Right)),
+            Self::CornerBotLeft => Some((SplitDirection::Below, SplitDirection::Left)),
+            Self::CornerBotRight => Some((SplitDirection::Below, SplitDirection::Right)),
+            _ => None,
+        }
+    }
+}
 
-        Ok([
-            top_full_hor,
-            right_full_ver,
-            bot_full_hor,
-            left_full_ver,
-            split_top,
-            split_right,
-            split_bot,
-            split_left,
-            center,
-        ])
+/// A rectangle of cells in the editor grid
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Region {
+    pub(crate) row: u32,
+    pub(crate) col: u32,
+    pub(crate) width: u32,
+    pub(crate) height: u32,
+}
+
+/// A direction to move the hovered position in, in the relative preview
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum Direction {
+    Left,
+    Down,
+    Up,
+    Right,
+}
+
+impl Direction {
+    /// How far `to` is from `from` in this direction, along and across it, `None` if `to` isn't
+    /// in this direction at all
+    pub(crate) fn distance(self, from: &ScreenPoint, to: &ScreenPoint) -> Option<(f64, f64)> {
+        let dx = to.x - from.x;
+        let dy = to.y - from.y;
+        let (along, across) = match self {
+            Self::Left => (-dx, dy),
+            Self::Right => (dx, dy),
+            Self::Up => (-dy, dx),
+            Self::Down => (dy, dx),
+        };
+        (along > 0.0).then_some((along, across.abs()))
     }
 }
 
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum SplitAxis {
+    Rows,
+    Cols,
+}
+
 pub(crate) struct ScreenPoint {
     pub(crate) x: f64,
     pub(crate) y: f64,
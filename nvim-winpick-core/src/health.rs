@@ -0,0 +1,289 @@
+use std::cell::RefCell;
+use std::path::{Path, PathBuf};
+
+use nvim_oxi::{Array, Dictionary};
+
+use crate::hint::Hint;
+use crate::opts::Opts;
+
+/// The newest `neovim-0-*` feature nvim-oxi is built with, see the workspace manifest
+const BUILT_AGAINST: &str = "0.11";
+/// The oldest version the bindings support
+const OLDEST_SUPPORTED: &str = "0.10";
+
+#[derive(Clone, Copy)]
+enum Level {
+    Ok,
+    Info,
+    Warn,
+    Error,
+}
+
+/// One line of `:checkhealth nvim_winpick`, `lua/nvim_winpick/health.lua` passes it on to
+/// `vim.health`
+struct Report {
+    section: String,
+    level: Level,
+    msg: String,
+    advice: Option<String>,
+}
+
+enum SetupStatus {
+    NotRun,
+    Done,
+    Failed(String),
+}
+
+thread_local! {
+    static SETUP_STATUS: RefCell<SetupStatus> = const { RefCell::new(SetupStatus::NotRun) };
+}
+
+/// Remembers how the last `setup` call went, a failed call keeps the opts from before it
+pub(crate) fn record_setup(error: Option<&str>) {
+    SETUP_STATUS.set(match error {
+        None => SetupStatus::Done,
+        Some(msg) => SetupStatus::Failed(msg.to_string()),
+    });
+}
+
+/// `{ { section = <name>, level = "ok" | "info" | "warn" | "error", msg = <msg>, advice = <advice>? } }`
+pub(crate) fn check() -> Array {
+    let mut reports = Vec::new();
+    check_version(&mut reports);
+    check_build(&mut reports);
+    check_setup(&mut reports);
+    match crate::opts::setup_opts() {
+        Some((opts, profiles)) => {
+            check_opts("opts", &opts, &mut reports);
+            for (name, profile) in profiles {
+                check_opts(&format!("profile '{name}'"), &profile, &mut reports);
+            }
+        }
+        None => check_opts("opts", &Opts::default(), &mut reports),
+    }
+    reports
+        .into_iter()
+        .map(|report| {
+            let mut dict = Dictionary::new();
+            dict.insert("section", report.section);
+            dict.insert(
+                "level",
+                match report.level {
+                    Level::Ok => "ok",
+                    Level::Info => "info",
+                    Level::Warn => "warn",
+                    Level::Error => "error",
+                },
+            );
+            dict.insert("msg", report.msg);
+            if let Some(advice) = report.advice {
+                dict.insert("advice", advice);
+            }
+            dict
+        })
+        .collect()
+}
+
+fn report(reports: &mut Vec<Report>, section: &str, level: Level, msg: impl Into<String>) {
+    reports.push(Report {
+        section: section.to_string(),
+        level,
+        msg: msg.into(),
+        advice: None,
+    });
+}
+
+fn report_with_advice(
+    reports: &mut Vec<Report>,
+    section: &str,
+    level: Level,
+    msg: impl Into<String>,
+    advice: impl Into<String>,
+) {
+    reports.push(Report {
+        section: section.to_string(),
+        level,
+        msg: msg.into(),
+        advice: Some(advice.into()),
+    });
+}
+
+fn has(feature: &str) -> bool {
+    nvim_oxi::api::call_function::<_, i64>("has", (feature,)).is_ok_and(|has| has == 1)
+}
+
+fn check_version(reports: &mut Vec<Report>) {
+    let section = "neovim version";
+    if has(&format!("nvim-{BUILT_AGAINST}")) {
+        report(
+            reports,
+            section,
+            Level::Ok,
+            format!("neovim {BUILT_AGAINST} or newer, matching the bindings"),
+        );
+    } else if has(&format!("nvim-{OLDEST_SUPPORTED}")) {
+        report_with_advice(
+            reports,
+            section,
+            Level::Warn,
+            format!("neovim {OLDEST_SUPPORTED}, the bindings are built for {BUILT_AGAINST}"),
+            format!("some functions may fail, upgrade to neovim {BUILT_AGAINST} or newer"),
+        );
+    } else {
+        report_with_advice(
+            reports,
+            section,
+            Level::Error,
+            format!("neovim older than {OLDEST_SUPPORTED} isn't supported"),
+            format!("upgrade to neovim {BUILT_AGAINST} or newer"),
+        );
+    }
+}
+
+fn check_build(reports: &mut Vec<Report>) {
+    let section = "build";
+    report(
+        reports,
+        section,
+        Level::Info,
+        format!(
+            "nvim_winpick {}, {} build",
+            env!("CARGO_PKG_VERSION"),
+            if cfg!(debug_assertions) {
+                "debug"
+            } else {
+                "release"
+            }
+        ),
+    );
+    let Some(root) = plugin_root() else {
+        report_with_advice(
+            reports,
+            section,
+            Level::Warn,
+            "the library isn't on the runtimepath as lua/nvim_winpick.so",
+            "see Install.md, the library has to be copied to <dir>/lua/ and <dir> added to the runtimepath",
+        );
+        return;
+    };
+    let branch = git_branch(&root);
+    let from = branch
+        .as_deref()
+        .map_or(String::new(), |branch| format!(" on branch '{branch}'"));
+    // The prebuilt branches only keep the library and the lua files
+    if root.join("build.lua").is_file() {
+        report(
+            reports,
+            section,
+            Level::Ok,
+            format!("built from source at {}{from}", root.display()),
+        );
+    } else {
+        report(
+            reports,
+            section,
+            Level::Ok,
+            format!("prebuilt library at {}{from}", root.display()),
+        );
+    }
+}
+
+/// The directory containing `lua/nvim_winpick.so`
+fn plugin_root() -> Option<PathBuf> {
+    let ext = if cfg!(windows) { "dll" } else { "so" };
+    let lib = nvim_oxi::api::get_runtime_file(format!("lua/nvim_winpick.{ext}"), false)
+        .ok()?
+        .next()?;
+    Some(lib.parent()?.parent()?.to_path_buf())
+}
+
+fn git_branch(root: &Path) -> Option<String> {
+    let head = std::fs::read_to_string(root.join(".git").join("HEAD")).ok()?;
+    head.trim()
+        .strip_prefix("ref: refs/heads/")
+        .map(ToString::to_string)
+}
+
+fn check_setup(reports: &mut Vec<Report>) {
+    let section = "setup";
+    SETUP_STATUS.with_borrow(|status| match status {
+        SetupStatus::NotRun => report_with_advice(
+            reports,
+            section,
+            Level::Info,
+            "setup hasn't run, the defaults are used",
+            "setup is only needed to change the defaults, or for keymaps",
+        ),
+        SetupStatus::Done => report(reports, section, Level::Ok, "setup ran"),
+        SetupStatus::Failed(msg) => report_with_advice(
+            reports,
+            section,
+            Level::Error,
+            format!("the last setup call failed: {msg}"),
+            "the opts from before it are still in use, fix the reported fields and run setup again",
+        ),
+    });
+}
+
+fn check_opts(section: &str, opts: &Opts, reports: &mut Vec<Report>) {
+    let mut fine = true;
+    if matches!(opts.hint, Hint::FloatingBigLetter) {
+        let missing: String = opts
+            .selection_chars
+            .chars()
+            .filter(|ch| crate::chars::char_to_lines(*ch).is_err())
+            .collect();
+        if !missing.is_empty() {
+            fine = false;
+            report_with_advice(
+                reports,
+                section,
+                Level::Error,
+                format!("'selection_chars' has chars without a big letter glyph: '{missing}'"),
+                "replace them, or use hint = \"floating-letter\"",
+            );
+        }
+    }
+    // Picks ignore case, so these can't be told apart
+    let chars: Vec<char> = opts.selection_chars.chars().collect();
+    let ambiguous: String = chars
+        .iter()
+        .enumerate()
+        .filter(|(i, ch)| chars[..*i].iter().any(|prev| prev.eq_ignore_ascii_case(ch)))
+        .map(|(_, ch)| *ch)
+        .collect();
+    if !ambiguous.is_empty() {
+        fine = false;
+        report(
+            reports,
+            section,
+            Level::Warn,
+            format!("'selection_chars' repeats '{ambiguous}', picks ignore case"),
+        );
+    }
+    if let Some((name, ch)) = opts.multiselect_overlap() {
+        fine = false;
+        report_with_advice(
+            reports,
+            section,
+            Level::Error,
+            format!("'multiselect.{name}' '{ch}' is also a selection char"),
+            "pick a 'multiselect' char that isn't in 'selection_chars'",
+        );
+    }
+    if fine {
+        report(
+            reports,
+            section,
+            Level::Ok,
+            format!(
+                "{} selection chars, all usable with the '{}' hint",
+                chars.len(),
+                match opts.hint {
+                    Hint::FloatingBigLetter => "floating-big-letter",
+                    Hint::FloatingLetter => "floating-letter",
+                }
+            ),
+        );
+    }
+}
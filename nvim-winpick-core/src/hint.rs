@@ -1,18 +1,11 @@
-#[derive(Default, Clone, Copy, Debug)]
+use schemars::JsonSchema;
+use serde::Deserialize;
+
+#[derive(Default, Clone, Copy, Debug, Deserialize, JsonSchema)]
 #[cfg_attr(feature = "test", derive(Eq, PartialEq))]
+#[serde(rename_all = "kebab-case")]
 pub enum Hint {
     #[default]
     FloatingBigLetter,
     FloatingLetter,
 }
-
-impl Hint {
-    pub(crate) fn from_str(test: &str) -> anyhow::Result<Self> {
-        let matched = match test {
-            "floating-big-letter" => Self::FloatingBigLetter,
-            "floating-letter" => Self::FloatingLetter,
-            unk => anyhow::bail!("unknown hint {unk}"),
-        };
-        Ok(matched)
-    }
-}
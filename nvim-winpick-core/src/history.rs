@@ -0,0 +1,339 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+
+use anyhow::{bail, Context, Result};
+use nvim_oxi::api::{
+    types::{SplitDirection, WindowConfig, WindowRelativeTo},
+    Buffer, TabPage, Window,
+};
+
+use crate::layout::Layout;
+use crate::notify::notify_warn;
+use crate::win::{is_floating, split_config};
+
+/// How many operations are kept per tabpage before the oldest are dropped
+const HISTORY_LIMIT: usize = 50;
+
+/// The arrangement of a tabpage: its split layout, what every window shows, and its floats
+struct Snapshot {
+    tab: TabPage,
+    layout: Layout,
+    /// In the same order as [`Layout::leaves`]
+    leaves: Vec<LeafState>,
+    floats: Vec<FloatState>,
+    current: Window,
+}
+
+#[derive(PartialEq)]
+struct LeafState {
+    window: Window,
+    buf: Buffer,
+    width: u32,
+    height: u32,
+}
+
+struct FloatState {
+    window: Window,
+    buf: Buffer,
+    cfg: Box<WindowConfig>,
+}
+
+#[derive(Default)]
+struct History {
+    undo: Vec<Snapshot>,
+    redo: Vec<Snapshot>,
+}
+
+thread_local! {
+    /// Keyed by tabpage handle, Neovim calls into the plugin from a single thread
+    static HISTORY: RefCell<HashMap<i32, History>> = RefCell::new(HashMap::new());
+}
+
+/// Runs `action` and records the arrangement of the current tabpage from before it, if the
+/// action changed it, so it can be undone
+pub(crate) fn recorded<T>(action: impl FnOnce() -> anyhow::Result<T>) -> anyhow::Result<T> {
+    // Failing to snapshot shouldn't stop the action, it just can't be undone
+    let before = Snapshot::take(&nvim_oxi::api::get_current_tabpage()).ok();
+    let res = action();
+    if let Some(before) = before.filter(|before| before.tab.is_valid()) {
+        let unchanged = Snapshot::take(&before.tab).is_ok_and(|after| after.same_as(&before));
+        if !unchanged {
+            HISTORY.with_borrow_mut(|history| {
+                let history = history.entry(before.tab.handle()).or_default();
+                push_limited(&mut history.undo, before);
+                history.redo.clear();
+            });
+        }
+    }
+    res
+}
+
+/// Restores the current tabpage to before its most recent operation
+pub(crate) fn undo() -> anyhow::Result<()> {
+    step(true)
+}
+
+/// Reapplies the most recently undone operation of the current tabpage
+pub(crate) fn redo() -> anyhow::Result<()> {
+    step(false)
+}
+
+fn step(undo: bool) -> anyhow::Result<()> {
+    let tab = nvim_oxi::api::get_current_tabpage();
+    let key = tab.handle();
+    let popped = HISTORY.with_borrow_mut(|history| {
+        let history = history.get_mut(&key)?;
+        if undo {
+            history.undo.pop()
+        } else {
+            history.redo.pop()
+        }
+    });
+    let Some(target) = popped else {
+        let what = if undo { "undo" } else { "redo" };
+        notify_warn(&format!("[nvim_winpick] nothing to {what}"));
+        return Ok(());
+    };
+    let current = Snapshot::take(&tab)?;
+    target.restore()?;
+    HISTORY.with_borrow_mut(|history| {
+        let history = history.entry(key).or_default();
+        let other = if undo {
+            &mut history.redo
+        } else {
+            &mut history.undo
+        };
+        push_limited(other, current);
+    });
+    Ok(())
+}
+
+fn push_limited(stack: &mut Vec<Snapshot>, snapshot: Snapshot) {
+    if stack.len() >= HISTORY_LIMIT {
+        stack.remove(0);
+    }
+    stack.push(snapshot);
+}
+
+impl Snapshot {
+    fn take(tab: &TabPage) -> Result<Self> {
+        let layout = Layout::of_tab(tab)?;
+        let mut leaves = Vec::new();
+        for window in layout.leaves() {
+            leaves.push(LeafState {
+                buf: window.get_buf().context("failed to get window buffer")?,
+                width: window.get_width().context("failed to get window width")?,
+                height: window.get_height().context("failed to get window height")?,
+                window,
+            });
+        }
+        let mut floats = Vec::new();
+        for window in tab.list_wins().context("failed to list tab windows")? {
+            let Ok(cfg) = window.get_config() else {
+                continue;
+            };
+            // Unfocusable floats are notifications and the like, not part of the arrangement
+            if cfg.relative.is_none() || cfg.focusable == Some(false) {
+                continue;
+            }
+            floats.push(FloatState {
+                buf: window.get_buf().context("failed to get float buffer")?,
+                cfg: Box::new(cfg),
+                window,
+            });
+        }
+        Ok(Self {
+            tab: tab.clone(),
+            current: tab.get_win().context("failed to get tab window")?,
+            layout,
+            leaves,
+            floats,
+        })
+    }
+
+    /// Focus and cursors don't count, only what's shown where
+    fn same_as(&self, other: &Self) -> bool {
+        self.layout == other.layout
+            && self.leaves == other.leaves
+            && self.floats.len() == other.floats.len()
+            && self
+                .floats
+                .iter()
+                .zip(&other.floats)
+                .all(|(a, b)| a.window == b.window && a.buf == b.buf)
+    }
+
+    /// Rearranges the tabpage to match the snapshot. Windows are moved rather than recreated
+    /// where they still exist, closed windows are reopened if their buffer is still around,
+    /// and windows that weren't there are closed.
+    fn restore(&self) -> Result<()> {
+        if !self.tab.is_valid() {
+            bail!("the tabpage no longer exists");
+        }
+        nvim_oxi::api::set_current_tabpage(&self.tab).context("failed to switch tab")?;
+        let leaf_bufs: HashMap<Window, Buffer> = self
+            .leaves
+            .iter()
+            .map(|leaf| (leaf.window.clone(), leaf.buf.clone()))
+            .collect();
+        let mut reopened: HashMap<Window, Window> = HashMap::new();
+        // Windows moved off to another tab where they're the last split can't be moved back, that
+        // would leave the tab without windows, they're reopened here and closed there instead
+        let mut strays = Vec::new();
+        let layout = self.layout.resolve(&mut |window| {
+            if window.is_valid() {
+                if !self.is_last_split_elsewhere(window) {
+                    return Ok(Some(window.clone()));
+                }
+                strays.push(window.clone());
+            }
+            let Some(buf) = leaf_bufs.get(window).filter(|buf| buf.is_valid()) else {
+                return Ok(None);
+            };
+            let new = nvim_oxi::api::open_win(buf, false, &parked_config())
+                .context("failed to reopen window")?;
+            reopened.insert(window.clone(), new.clone());
+            Ok(Some(new))
+        })?;
+        let Some(layout) = layout else {
+            bail!("none of the windows can be restored");
+        };
+        let leaves = layout.leaves();
+        let Some(mut anchor) = layout.first_leaf() else {
+            bail!("none of the windows can be restored");
+        };
+        // Everything else is parked as a float, so the anchor has to be a split in this tab
+        if is_floating(&anchor) || anchor.get_tabpage().ok().as_ref() != Some(&self.tab) {
+            anchor
+                .set_config(&split_config(Window::from(-1), SplitDirection::Right))
+                .context("failed to dock window")?;
+        }
+        let floats: Vec<&Window> = self.floats.iter().map(|float| &float.window).collect();
+        for mut window in self.tab.list_wins().context("failed to list tab windows")? {
+            if window == anchor {
+                continue;
+            }
+            if leaves.contains(&window) {
+                if !is_floating(&window) {
+                    window
+                        .set_config(&parked_config())
+                        .context("failed to park window")?;
+                }
+            } else if !floats.contains(&&window)
+                && window
+                    .get_config()
+                    .is_ok_and(|cfg| cfg.relative.is_none() || cfg.focusable != Some(false))
+            {
+                // Hiding keeps buffers with unsaved changes around
+                window.hide().context("failed to close window")?;
+            }
+        }
+        build(&layout)?;
+        for float in &self.floats {
+            if float.window.is_valid() {
+                float
+                    .window
+                    .clone()
+                    .set_config(&float.cfg)
+                    .context("failed to restore float")?;
+            } else if float.buf.is_valid() {
+                nvim_oxi::api::open_win(&float.buf, false, &float.cfg)
+                    .context("failed to reopen float")?;
+            }
+        }
+        for leaf in &self.leaves {
+            let Some(mut window) = reopened
+                .get(&leaf.window)
+                .cloned()
+                .or_else(|| leaf.window.is_valid().then(|| leaf.window.clone()))
+            else {
+                continue;
+            };
+            if leaf.buf.is_valid() && window.get_buf().ok().as_ref() != Some(&leaf.buf) {
+                window
+                    .set_buf(&leaf.buf)
+                    .context("failed to restore buffer")?;
+            }
+        }
+        for stray in strays {
+            // Hiding keeps buffers with unsaved changes around, and closes the tab it was left in
+            stray.hide().context("failed to close moved window")?;
+        }
+        self.restore_sizes(&reopened)?;
+        let current = reopened.get(&self.current).unwrap_or(&self.current);
+        if current.is_valid() {
+            nvim_oxi::api::set_current_win(current).context("failed to restore focus")?;
+        }
+        Ok(())
+    }
+
+    /// `reopened` maps the snapshot's windows to the ones that were opened in their place
+    fn restore_sizes(&self, reopened: &HashMap<Window, Window>) -> Result<()> {
+        // Resizing one window pushes its neighbours around, a second pass settles it like
+        // `winrestcmd()` does
+        for _ in 0..2 {
+            for leaf in &self.leaves {
+                let window = reopened.get(&leaf.window).unwrap_or(&leaf.window);
+                if window.is_valid() {
+                    let mut window = window.clone();
+                    window.set_width(leaf.width).with_context(|| {
+                        format!("failed to restore width of {}", window.handle())
+                    })?;
+                    window.set_height(leaf.height).with_context(|| {
+                        format!("failed to restore height of {}", window.handle())
+                    })?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Whether `window` is in another tabpage, without other splits next to it
+    fn is_last_split_elsewhere(&self, window: &Window) -> bool {
+        let Ok(tab) = window.get_tabpage() else {
+            return false;
+        };
+        tab != self.tab
+            && !is_floating(window)
+            && tab
+                .list_wins()
+                .is_ok_and(|mut wins| !wins.any(|win| &win != window && !is_floating(&win)))
+    }
+}
+
+/// Where windows wait while the layout is rebuilt, they're moved into place right after
+fn parked_config() -> WindowConfig {
+    WindowConfig::builder()
+        .relative(WindowRelativeTo::Editor)
+        .width(1)
+        .height(1)
+        .row(0.0)
+        .col(0.0)
+        .build()
+}
+
+/// Splits the first window of every child off the first window of the node, then does the
+/// same within every child. The first window of `layout` has to be in place already.
+fn build(layout: &Layout) -> Result<()> {
+    let (children, direction) = match layout {
+        Layout::Leaf(_) => return Ok(()),
+        Layout::Row(children) => (children, SplitDirection::Right),
+        Layout::Col(children) => (children, SplitDirection::Below),
+    };
+    let mut prev = None;
+    for child in children {
+        let Some(mut window) = child.first_leaf() else {
+            continue;
+        };
+        if let Some(prev) = prev {
+            window
+                .set_config(&split_config(prev, direction))
+                .context("failed to move window into place")?;
+        }
+        prev = Some(window);
+    }
+    for child in children {
+        build(child)?;
+    }
+    Ok(())
+}
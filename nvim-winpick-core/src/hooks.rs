@@ -0,0 +1,172 @@
+use std::borrow::Cow;
+use std::cell::RefCell;
+
+use nvim_oxi::api::opts::ExecAutocmdsOpts;
+use nvim_oxi::api::Window;
+use nvim_oxi::conversion::FromObject;
+use nvim_oxi::{Array, Dictionary, Function, Object};
+use schemars::{json_schema, JsonSchema, Schema, SchemaGenerator};
+use serde::{Deserialize, Deserializer};
+
+use crate::notify::notify_error;
+
+/// A lua function from the opts, called with a table describing the event.
+/// Kept untyped since a `Function<Dictionary, ()>` isn't `Send`, and the setup opts need to be
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "test", derive(Eq, PartialEq))]
+pub struct Hook(pub(crate) Function<(), ()>);
+
+impl<'de> Deserialize<'de> for Hook {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Function::deserialize(deserializer).map(Self)
+    }
+}
+
+/// Only a lua config can set one, JSON has nothing to describe it with
+impl JsonSchema for Hook {
+    fn schema_name() -> Cow<'static, str> {
+        "Hook".into()
+    }
+
+    fn json_schema(_: &mut SchemaGenerator) -> Schema {
+        json_schema!({ "$comment": "a lua function, called with a table describing the event" })
+    }
+}
+
+impl Hook {
+    fn call(&self, name: &str, event: &Dictionary) {
+        let res = Function::<Dictionary, ()>::from_object(Object::from(self.0.clone()))
+            .map_err(anyhow::Error::from)
+            .and_then(|fun| fun.call(event.clone()).map_err(anyhow::Error::from));
+        // A broken hook shouldn't stop the action
+        if let Err(e) = res {
+            notify_error(&format!("[nvim_winpick] '{name}' hook failed: {e:#?}"));
+        }
+    }
+}
+
+/// Callbacks around picks, called with `{ action = <name> }`, and `picked = { <winid> }` once
+/// there are picks. `on_action_done` also gets `new_win = <winid>` if the action opened a window
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "test", derive(Eq, PartialEq))]
+pub struct Hooks {
+    /// Before a pick, when the hints are about to be drawn
+    pub on_pick_start: Option<Hook>,
+    /// After a pick, with the windows picked by it
+    pub on_pick: Option<Hook>,
+    /// After a pick that ended without a window
+    pub on_cancel: Option<Hook>,
+    /// After the action went through, with every window picked during it
+    pub on_action_done: Option<Hook>,
+}
+
+/// An action that's running, picks made while it runs are added to it
+struct Running {
+    action: &'static str,
+    picked: Vec<Window>,
+    cancelled: bool,
+}
+
+thread_local! {
+    /// A stack, since a hook can start another action
+    static RUNNING: RefCell<Vec<Running>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Runs the action named `action`, firing `User WinpickPre` before it, and `on_action_done` and
+/// `User WinpickPost` after it. Post always follows Pre, with `cancelled = true` if a pick was
+/// cancelled or the action failed.
+pub(crate) fn around<T>(
+    action: &'static str,
+    hooks: &Hooks,
+    run: impl FnOnce() -> anyhow::Result<T>,
+) -> anyhow::Result<T> {
+    let before: Vec<Window> = nvim_oxi::api::list_wins().collect();
+    fire_user("WinpickPre", event(Some(action)));
+    RUNNING.with_borrow_mut(|running| {
+        running.push(Running {
+            action,
+            picked: Vec::new(),
+            cancelled: false,
+        });
+    });
+    let res = run();
+    let Some(running) = RUNNING.with_borrow_mut(Vec::pop) else {
+        return res;
+    };
+    let mut event = event(Some(action));
+    event.insert("picked", winids(&running.picked));
+    if let Some(new_win) = nvim_oxi::api::list_wins().find(|win| !before.contains(win)) {
+        event.insert("new_win", new_win.handle());
+    }
+    let done = res.is_ok() && !running.cancelled;
+    if done {
+        if let Some(hook) = &hooks.on_action_done {
+            hook.call("on_action_done", &event);
+        }
+    }
+    event.insert("cancelled", !done);
+    fire_user("WinpickPost", event);
+    res
+}
+
+/// Reports that a pick is about to start
+pub(crate) fn pick_started(hooks: &Hooks) {
+    if let Some(hook) = &hooks.on_pick_start {
+        let action = RUNNING.with_borrow(|running| running.last().map(|running| running.action));
+        hook.call("on_pick_start", &event(action));
+    }
+}
+
+/// Reports the windows a pick ended with, none means it was cancelled
+pub(crate) fn picked(hooks: &Hooks, windows: &[Window]) {
+    let action = RUNNING.with_borrow_mut(|running| {
+        let running = running.last_mut()?;
+        if windows.is_empty() {
+            running.cancelled = true;
+        } else {
+            running.picked.extend(windows.iter().cloned());
+        }
+        Some(running.action)
+    });
+    let mut event = event(action);
+    event.insert("picked", winids(windows));
+    let hook = if windows.is_empty() {
+        hooks.on_cancel.as_ref().map(|hook| (hook, "on_cancel"))
+    } else {
+        hooks.on_pick.as_ref().map(|hook| (hook, "on_pick"))
+    };
+    if let Some((hook, name)) = hook {
+        hook.call(name, &event);
+    }
+}
+
+/// Reports that a prompt following the window picks, for a position, size, tab or stashed
+/// window, was cancelled, which cancels the running action like a cancelled pick does
+pub(crate) fn cancelled(hooks: &Hooks) {
+    picked(hooks, &[]);
+}
+
+fn event(action: Option<&str>) -> Dictionary {
+    let mut event = Dictionary::new();
+    if let Some(action) = action {
+        event.insert("action", action);
+    }
+    event
+}
+
+fn winids(windows: &[Window]) -> Array {
+    windows.iter().map(Window::handle).collect()
+}
+
+fn fire_user(pattern: &str, data: Dictionary) {
+    let opts = ExecAutocmdsOpts::builder()
+        .patterns(pattern)
+        .data(data)
+        .modeline(false)
+        .build();
+    if let Err(e) = nvim_oxi::api::exec_autocmds(["User"], &opts) {
+        notify_error(&format!(
+            "[nvim_winpick] failed to fire 'User {pattern}': {e:#?}"
+        ));
+    }
+}
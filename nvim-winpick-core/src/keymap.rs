@@ -0,0 +1,373 @@
+use std::borrow::Cow;
+use std::cell::RefCell;
+use std::fmt;
+
+use anyhow::Context as _;
+use nvim_oxi::api::{opts::SetKeymapOpts, types::Mode};
+use schemars::{json_schema, JsonSchema, Schema, SchemaGenerator};
+use serde::de::value::MapAccessDeserializer;
+use serde::de::{self, Error as _, IgnoredAny, MapAccess, SeqAccess, Unexpected, Visitor};
+use serde::{Deserialize, Deserializer};
+
+use crate::ctx::with_count;
+
+/// A `<Plug>(winpick-<name>)` mapping, and what the default preset binds it to
+struct PlugMap {
+    name: &'static str,
+    default_lhs: &'static str,
+    desc: &'static str,
+    action: fn(),
+}
+
+const PLUG_MAPS: [PlugMap; 13] = [
+    PlugMap {
+        name: "focus",
+        default_lhs: "<leader>wf",
+        desc: "Pick a window to focus",
+        action: || crate::pick_focus_window(None),
+    },
+    PlugMap {
+        name: "swap",
+        default_lhs: "<leader>ws",
+        desc: "Pick a window to swap with",
+        action: || crate::pick_swap_window(None),
+    },
+    PlugMap {
+        name: "close",
+        default_lhs: "<leader>wc",
+        desc: "Pick a window to close",
+        action: || {
+            let _ = crate::pick_close_window(None);
+        },
+    },
+    PlugMap {
+        name: "only",
+        default_lhs: "<leader>wo",
+        desc: "Pick a window to keep, closing the others",
+        action: || {
+            let _ = crate::pick_only_windows(None);
+        },
+    },
+    PlugMap {
+        name: "stash",
+        default_lhs: "<leader>wz",
+        desc: "Pick a window to stash",
+        action: || crate::pick_stash_window(None),
+    },
+    PlugMap {
+        name: "unstash",
+        default_lhs: "<leader>wZ",
+        desc: "Restore the last stashed window",
+        action: || crate::unstash(None),
+    },
+    PlugMap {
+        name: "float",
+        default_lhs: "<leader>wF",
+        desc: "Pick a window to float",
+        action: || crate::pick_float_window(None),
+    },
+    PlugMap {
+        name: "dock",
+        default_lhs: "<leader>wd",
+        desc: "Pick a float to dock",
+        action: || crate::pick_dock_float(None),
+    },
+    PlugMap {
+        name: "tab",
+        default_lhs: "<leader>wt",
+        desc: "Pick a window to move to another tab",
+        action: || crate::pick_move_to_tab(None),
+    },
+    PlugMap {
+        name: "undo",
+        default_lhs: "<leader>wu",
+        desc: "Undo the last window operation",
+        action: crate::winpick_undo,
+    },
+    PlugMap {
+        name: "redo",
+        default_lhs: "<leader>wr",
+        desc: "Redo the last undone window operation",
+        action: crate::winpick_redo,
+    },
+    PlugMap {
+        name: "repeat",
+        default_lhs: "<leader>w.",
+        desc: "Repeat the last window operation",
+        action: || crate::repeat_last(None),
+    },
+    PlugMap {
+        name: "operator",
+        default_lhs: "<leader>wg",
+        desc: "Run the action of the next key on a picked window, a count picks it",
+        action: crate::pick_operator,
+    },
+];
+
+thread_local! {
+    /// The keys `setup` bound and the `<Plug>` name they're bound to, so they can be unbound
+    /// when it runs again
+    static BOUND: RefCell<Vec<(String, &'static str)>> = const { RefCell::new(Vec::new()) };
+}
+
+/// The `<Plug>` mappings that pick a window first, which `<Plug>(winpick-operator)` runs
+const OPERATOR_TARGETS: [&str; 8] = [
+    "focus", "swap", "close", "only", "stash", "float", "dock", "tab",
+];
+
+/// The key `<Plug>(winpick-operator)` takes for each of its actions, the last key of the action's
+/// default mapping, with the action's name
+pub(crate) fn operator_targets() -> impl Iterator<Item = (char, &'static str, fn())> {
+    PLUG_MAPS
+        .iter()
+        .filter(|plug| OPERATOR_TARGETS.contains(&plug.name))
+        .filter_map(|plug| Some((plug.default_lhs.chars().last()?, plug.name, plug.action)))
+}
+
+/// Names of the `<Plug>` mappings, which `keymaps` can bind
+pub(crate) const PLUG_NAMES: [&str; PLUG_MAPS.len()] = {
+    let mut names = [""; PLUG_MAPS.len()];
+    let mut i = 0;
+    while i < PLUG_MAPS.len() {
+        names[i] = PLUG_MAPS[i].name;
+        i += 1;
+    }
+    names
+};
+
+/// Which `<Plug>` mappings `setup` binds keys to
+#[derive(Debug, Default)]
+pub(crate) enum Keymaps {
+    #[default]
+    None,
+    Default,
+    /// Plug name and the key bound to it
+    Custom(Vec<(&'static str, String)>),
+}
+
+/// `false`, `"default"`, or `{ <name> = <lhs> | false }`
+impl<'de> Deserialize<'de> for Keymaps {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct KeymapsVisitor;
+
+        impl<'de> Visitor<'de> for KeymapsVisitor {
+            type Value = Keymaps;
+
+            fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str("false, \"default\" or a table")
+            }
+
+            fn visit_bool<E: de::Error>(self, b: bool) -> Result<Self::Value, E> {
+                if b {
+                    return Err(E::invalid_value(Unexpected::Bool(b), &self));
+                }
+                Ok(Keymaps::None)
+            }
+
+            fn visit_str<E: de::Error>(self, preset: &str) -> Result<Self::Value, E> {
+                if preset != "default" {
+                    return Err(E::unknown_variant(preset, &["default"]));
+                }
+                Ok(Keymaps::Default)
+            }
+
+            fn visit_map<A: MapAccess<'de>>(self, map: A) -> Result<Self::Value, A::Error> {
+                Bound::deserialize(MapAccessDeserializer::new(map))
+                    .map(|bound| Keymaps::Custom(bound.0))
+            }
+
+            // An empty lua table is an array
+            fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+                if seq.next_element::<IgnoredAny>()?.is_some() {
+                    return Err(A::Error::invalid_type(Unexpected::Seq, &self));
+                }
+                Ok(Keymaps::Custom(Vec::new()))
+            }
+        }
+
+        deserializer.deserialize_any(KeymapsVisitor)
+    }
+}
+
+impl JsonSchema for Keymaps {
+    fn inline_schema() -> bool {
+        true
+    }
+
+    fn schema_name() -> Cow<'static, str> {
+        "KeymapPreset".into()
+    }
+
+    fn json_schema(generator: &mut SchemaGenerator) -> Schema {
+        json_schema!({
+            "anyOf": [
+                { "type": "boolean" },
+                { "enum": ["default"] },
+                generator.subschema_for::<Bound>(),
+            ]
+        })
+    }
+}
+
+/// The keys of a `keymaps` table, with their plug name. Keys set to `false` are left out
+struct Bound(Vec<(&'static str, String)>);
+
+impl<'de> Deserialize<'de> for Bound {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct BoundVisitor;
+
+        impl<'de> Visitor<'de> for BoundVisitor {
+            type Value = Bound;
+
+            fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str("a table of <Plug> names to keys")
+            }
+
+            fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+                let mut bound = Vec::new();
+                while let Some((name, lhs)) = map.next_entry::<PlugName, Lhs>()? {
+                    if let Some(lhs) = lhs.0 {
+                        bound.push((name.0, lhs));
+                    }
+                }
+                Ok(Bound(bound))
+            }
+        }
+
+        deserializer.deserialize_map(BoundVisitor)
+    }
+}
+
+impl JsonSchema for Bound {
+    fn schema_name() -> Cow<'static, str> {
+        "Keymaps".into()
+    }
+
+    fn json_schema(_: &mut SchemaGenerator) -> Schema {
+        let lhs = json_schema!({ "type": ["string", "boolean"] });
+        let properties: serde_json::Map<String, serde_json::Value> = PLUG_NAMES
+            .iter()
+            .map(|name| ((*name).to_string(), lhs.clone().to_value()))
+            .collect();
+        json_schema!({
+            "type": "object",
+            "properties": properties,
+            "additionalProperties": false,
+        })
+    }
+}
+
+/// One of [`PLUG_NAMES`]
+struct PlugName(&'static str);
+
+impl<'de> Deserialize<'de> for PlugName {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct NameVisitor;
+
+        impl Visitor<'_> for NameVisitor {
+            type Value = PlugName;
+
+            fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str("a <Plug> name")
+            }
+
+            fn visit_str<E: de::Error>(self, name: &str) -> Result<Self::Value, E> {
+                PLUG_NAMES
+                    .iter()
+                    .find(|plug| **plug == name)
+                    .map(|plug| PlugName(plug))
+                    .ok_or_else(|| E::unknown_field(name, &PLUG_NAMES))
+            }
+        }
+
+        deserializer.deserialize_identifier(NameVisitor)
+    }
+}
+
+/// A key, or `false` to leave the mapping unbound
+struct Lhs(Option<String>);
+
+impl<'de> Deserialize<'de> for Lhs {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct LhsVisitor;
+
+        impl Visitor<'_> for LhsVisitor {
+            type Value = Lhs;
+
+            fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str("a key or false")
+            }
+
+            fn visit_bool<E: de::Error>(self, b: bool) -> Result<Self::Value, E> {
+                if b {
+                    return Err(E::invalid_value(Unexpected::Bool(b), &self));
+                }
+                Ok(Lhs(None))
+            }
+
+            fn visit_str<E: de::Error>(self, lhs: &str) -> Result<Self::Value, E> {
+                Ok(Lhs(Some(lhs.to_string())))
+            }
+        }
+
+        deserializer.deserialize_any(LhsVisitor)
+    }
+}
+
+/// Sets up every `<Plug>(winpick-<name>)` mapping and binds keys to them per `keymaps`,
+/// replacing the keys bound by an earlier `setup`. A count picks the window with that label
+/// position without drawing, `3<Plug>(winpick-focus)` focuses the third labelled window
+pub(crate) fn register(keymaps: &Keymaps) -> anyhow::Result<()> {
+    unbind()?;
+    for plug in &PLUG_MAPS {
+        let action = plug.action;
+        let opts = SetKeymapOpts::builder()
+            .callback(move |()| with_count(action))
+            .desc(plug.desc)
+            .noremap(true)
+            .silent(true)
+            .build();
+        nvim_oxi::api::set_keymap(Mode::Normal, &plug_lhs(plug.name), "", &opts)
+            .with_context(|| format!("failed to set up {}", plug_lhs(plug.name)))?;
+    }
+    let bound: Vec<(&'static str, &str)> = match keymaps {
+        Keymaps::None => return Ok(()),
+        Keymaps::Default => PLUG_MAPS
+            .iter()
+            .map(|plug| (plug.name, plug.default_lhs))
+            .collect(),
+        Keymaps::Custom(bound) => bound
+            .iter()
+            .map(|(name, lhs)| (*name, lhs.as_str()))
+            .collect(),
+    };
+    for (name, lhs) in bound {
+        let desc = PLUG_MAPS
+            .iter()
+            .find(|plug| plug.name == name)
+            .map_or("", |plug| plug.desc);
+        let opts = SetKeymapOpts::builder().desc(desc).silent(true).build();
+        nvim_oxi::api::set_keymap(Mode::Normal, lhs, &plug_lhs(name), &opts)
+            .with_context(|| format!("failed to map {lhs}"))?;
+        BOUND.with_borrow_mut(|bound| bound.push((lhs.to_string(), name)));
+    }
+    Ok(())
+}
+
+/// Removes the keys bound by an earlier `setup`, unless they've been mapped to something else
+/// since
+fn unbind() -> anyhow::Result<()> {
+    for (lhs, name) in BOUND.take() {
+        let rhs: String = nvim_oxi::api::call_function("maparg", (lhs.as_str(), "n"))
+            .with_context(|| format!("failed to look up {lhs}"))?;
+        if rhs == plug_lhs(name) {
+            nvim_oxi::api::del_keymap(Mode::Normal, &lhs)
+                .with_context(|| format!("failed to unmap {lhs}"))?;
+        }
+    }
+    Ok(())
+}
+
+fn plug_lhs(name: &str) -> String {
+    format!("<Plug>(winpick-{name})")
+}
@@ -0,0 +1,173 @@
+use anyhow::{bail, Context, Result};
+use nvim_oxi::api::{types::SplitDirection, TabPage, Window};
+use nvim_oxi::conversion::FromObject;
+use nvim_oxi::{Array, Object};
+
+/// The split layout of a tabpage as returned by `winlayout()`, floating windows aren't part of it
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Layout {
+    Leaf(Window),
+    /// Children are placed left to right
+    Row(Vec<Layout>),
+    /// Children are placed top to bottom
+    Col(Vec<Layout>),
+}
+
+impl Layout {
+    pub(crate) fn of_tab(tab: &TabPage) -> Result<Self> {
+        let tabnr = tab.get_number().context("failed to get tab number")?;
+        let obj: Object = nvim_oxi::api::call_function("winlayout", (tabnr,))
+            .context("failed to get window layout")?;
+        Self::from_object(obj)
+    }
+
+    fn from_object(obj: Object) -> Result<Self> {
+        let arr = Array::from_object(obj).context("layout node is not a list")?;
+        let mut items = arr.into_iter();
+        let (Some(kind), Some(content)) = (items.next(), items.next()) else {
+            bail!("layout node needs a kind and content");
+        };
+        let kind = String::from_object(kind).context("layout node kind is not a string")?;
+        let layout = match kind.as_str() {
+            "leaf" => Self::Leaf(Window::from_object(content).context("invalid leaf window")?),
+            "row" | "col" => {
+                let children = Array::from_object(content)
+                    .context("layout children are not a list")?
+                    .into_iter()
+                    .map(Self::from_object)
+                    .collect::<Result<Vec<_>>>()?;
+                if kind == "row" {
+                    Self::Row(children)
+                } else {
+                    Self::Col(children)
+                }
+            }
+            unk => bail!("unknown layout node kind {unk}"),
+        };
+        Ok(layout)
+    }
+
+    /// All windows in the layout, left to right and top to bottom
+    pub(crate) fn leaves(&self) -> Vec<Window> {
+        let mut leaves = Vec::new();
+        self.collect_leaves(&mut leaves);
+        leaves
+    }
+
+    fn collect_leaves(&self, leaves: &mut Vec<Window>) {
+        match self {
+            Self::Leaf(win) => leaves.push(win.clone()),
+            Self::Row(children) | Self::Col(children) => {
+                for child in children {
+                    child.collect_leaves(leaves);
+                }
+            }
+        }
+    }
+
+    /// The top left window of the layout
+    pub(crate) fn first_leaf(&self) -> Option<Window> {
+        match self {
+            Self::Leaf(win) => Some(win.clone()),
+            Self::Row(children) | Self::Col(children) => children.first()?.first_leaf(),
+        }
+    }
+
+    /// Replaces every window with the result of `resolve`, dropping windows it returns `None`
+    /// for, and containers left empty by that
+    pub(crate) fn resolve<F: FnMut(&Window) -> Result<Option<Window>>>(
+        &self,
+        resolve: &mut F,
+    ) -> Result<Option<Self>> {
+        let resolved = match self {
+            Self::Leaf(win) => resolve(win)?.map(Self::Leaf),
+            Self::Row(children) | Self::Col(children) => {
+                let mut resolved = Vec::with_capacity(children.len());
+                for child in children {
+                    if let Some(child) = child.resolve(resolve)? {
+                        resolved.push(child);
+                    }
+                }
+                match resolved.len() {
+                    0 => None,
+                    1 => resolved.pop(),
+                    _ if matches!(self, Self::Row(_)) => Some(Self::Row(resolved)),
+                    _ => Some(Self::Col(resolved)),
+                }
+            }
+        };
+        Ok(resolved)
+    }
+
+    /// Where to split to put `window` back in its current place after it's removed, as close as
+    /// the layout allows: next to a neighbouring window, or at the edge of the tabpage.
+    /// `None` if `window` isn't in the layout or is the only window.
+    pub(crate) fn placement_of(&self, window: &Window) -> Option<SplitPlacement> {
+        self.placement_within(window, true)
+    }
+
+    fn placement_within(&self, window: &Window, is_root: bool) -> Option<SplitPlacement> {
+        let (children, is_row) = match self {
+            Self::Leaf(_) => return None,
+            Self::Row(children) => (children, true),
+            Self::Col(children) => (children, false),
+        };
+        let Some(ind) = children
+            .iter()
+            .position(|child| matches!(child, Self::Leaf(win) if win == window))
+        else {
+            return children
+                .iter()
+                .find_map(|child| child.placement_within(window, false));
+        };
+        let (before, after) = if is_row {
+            (SplitDirection::Left, SplitDirection::Right)
+        } else {
+            (SplitDirection::Above, SplitDirection::Below)
+        };
+        let last = children.len() - 1;
+        // At the edge of the tabpage, a full-width/height split puts it back exactly
+        if is_root && (ind == 0 || ind == last) {
+            let direction = if ind == 0 { before } else { after };
+            return Some(SplitPlacement {
+                anchor: None,
+                direction,
+            });
+        }
+        if ind > 0 {
+            Some(SplitPlacement {
+                anchor: Some(children[ind - 1].edge_leaf(is_row, false)?),
+                direction: after,
+            })
+        } else {
+            Some(SplitPlacement {
+                anchor: Some(children.get(ind + 1)?.edge_leaf(is_row, true)?),
+                direction: before,
+            })
+        }
+    }
+
+    /// A window on the edge of this node, the first one or the last one along `horizontal`
+    fn edge_leaf(&self, horizontal: bool, first: bool) -> Option<Window> {
+        match self {
+            Self::Leaf(win) => Some(win.clone()),
+            Self::Row(children) | Self::Col(children) => {
+                let along = matches!(self, Self::Row(_)) == horizontal;
+                let child = if along && !first {
+                    children.last()
+                } else {
+                    children.first()
+                };
+                child?.edge_leaf(horizontal, first)
+            }
+        }
+    }
+}
+
+/// A split that puts a window back in place, `anchor` is the window to split, `None` splits at
+/// the top level of the tabpage
+#[derive(Debug, Clone)]
+pub(crate) struct SplitPlacement {
+    pub(crate) anchor: Option<Window>,
+    pub(crate) direction: SplitDirection,
+}
@@ -1,35 +1,92 @@
+use actions::with_action_defaults;
 use ctx::Context;
 use notify::notify_error;
-use nvim_oxi::{api::Window, Object};
+use nvim_oxi::conversion::FromObject;
+use nvim_oxi::{api::Window, Array, Dictionary, Object};
+use win::SplitKind;
+mod actions;
 mod buf;
 mod chars;
+mod command;
 mod ctx;
 mod draw;
 mod filter;
 mod geometry;
+mod health;
 mod hint;
+mod history;
+mod hooks;
+mod keymap;
+mod layout;
 mod notify;
+mod operator;
 mod opts;
 mod pick;
+mod repeat;
+mod schema;
+mod validate;
 mod win;
 
 pub use hint::Hint;
-pub use opts::{OpenOverOpts, OpenRelativeOpts, OpenSplitOpts, Opts};
+pub use hooks::{Hook, Hooks};
+pub use opts::{
+    CloseOpts, DockFloatOpts, MoveToTabOpts, OpenManyOpts, OpenOverOpts, OpenRelativeOpts,
+    OpenSplitOpts, Opts, PlaceRelativeOpts, RepeatOpts, UnstashOpts,
+};
 
 pub fn setup(opts: Option<Object>) {
-    let Some(opts) = safe_parse_opts(opts) else {
-        return;
+    // Can be called again, e.g. on config reloads, replacing the previous opts and profiles
+    let keymaps = match Opts::setup(opts.unwrap_or_default()) {
+        Ok(keymaps) => keymaps,
+        Err(e) => {
+            setup_failed(&format!("{e:#?}"));
+            return;
+        }
     };
-    if let Err(e) = Opts::setup_opts(opts) {
-        notify_error(&format!("[nvim_winpick] failed to setup {e:#?}"));
+    health::record_setup(None);
+    if let Err(e) = command::register() {
+        notify_error(&format!(
+            "[nvim_winpick] failed to register commands {e:#?}"
+        ));
+    }
+    if let Err(e) = keymap::register(&keymaps) {
+        notify_error(&format!("[nvim_winpick] failed to set up keymaps {e:#?}"));
+    }
+}
+
+/// Kept for `:checkhealth`, which is where people look once the notification is gone
+fn setup_failed(msg: &str) {
+    health::record_setup(Some(msg));
+    notify_error(&format!("[nvim_winpick] failed to setup {msg}"));
+}
+
+/// Reports for `:checkhealth nvim_winpick`, see `lua/nvim_winpick/health.lua`
+#[must_use]
+pub fn health() -> Array {
+    health::check()
+}
+
+/// The opts as a JSON schema with `"json"`, or as `---@class` annotations with `"lua"`
+#[must_use]
+pub fn schema(format: Option<Object>) -> Option<String> {
+    let generated = String::from_object(format.unwrap_or_default())
+        .map_err(anyhow::Error::from)
+        .and_then(|format| schema::Format::from_str(&format))
+        .map(schema::generate);
+    match generated {
+        Ok(schema) => Some(schema),
+        Err(e) => {
+            notify_error(&format!("[nvim_winpick] failed to generate schema {e:#?}"));
+            None
+        }
     }
 }
 
 #[must_use]
 pub fn pick_window(opts: Option<Object>) -> Option<Window> {
     let opts = safe_parse_opts(opts)?;
-    let mut ctx = Context::DEFAULT;
-    match pick::pick_window(&opts, &mut ctx) {
+    let mut ctx = Context::new(&opts);
+    match hooks::around("pick", &opts.hooks, || pick::pick_window(&opts, &mut ctx)) {
         Ok(v) => v,
         Err(e) => {
             notify_error(&format!("[nvim_winpick] failed to pick window {e:#?}"));
@@ -43,8 +100,10 @@ pub fn pick_multiple_windows(opts: Option<Object>) -> Vec<Window> {
     let Some(opts) = safe_parse_opts(opts) else {
         return vec![];
     };
-    let mut ctx = Context::DEFAULT;
-    match pick::try_pick_multi_window(&opts, &mut ctx) {
+    let mut ctx = Context::new(&opts);
+    match hooks::around("pick_multiple", &opts.hooks, || {
+        pick::try_pick_multi_window(&opts, &mut ctx)
+    }) {
         Ok(w) => w,
         Err(e) => {
             notify_error(&format!(
@@ -62,7 +121,7 @@ pub fn safe_parse_opts(opts: Option<Object>) -> Option<Opts> {
         return match opts::Opts::parse_obj(opts) {
             Ok(o) => Some(o),
             Err(e) => {
-                notify_error(&format!("[nvim_winpic] invalid opts: {e:#?}"));
+                notify_error(&format!("[nvim_winpick] failed to parse 'opts': {e:#?}"));
                 None
             }
         };
@@ -73,11 +132,13 @@ pub fn safe_parse_opts(opts: Option<Object>) -> Option<Opts> {
 }
 
 pub fn pick_focus_window(opts: Option<Object>) {
-    let Some(opts) = safe_parse_opts(opts) else {
+    let Some(opts) = safe_parse_opts(with_action_defaults("focus", opts)) else {
         return;
     };
-    let mut ctx = Context::DEFAULT;
-    match pick::simple_operations::pick_focus_window(&opts, &mut ctx) {
+    let mut ctx = Context::new(&opts);
+    match hooks::around("focus", &opts.hooks, || {
+        pick::simple_operations::pick_focus_window(&opts, &mut ctx)
+    }) {
         Ok(()) => {}
         Err(e) => {
             notify_error(&format!(
@@ -87,27 +148,69 @@ pub fn pick_focus_window(opts: Option<Object>) {
     }
 }
 
-pub fn pick_close_window(opts: Option<Object>) {
-    let Some(opts) = safe_parse_opts(opts) else {
-        return;
+/// Returns `{ closed = { <winid> }, skipped = { { win = <winid>, reason = <reason> } } }`
+#[must_use]
+pub fn pick_close_window(opts: Option<Object>) -> Dictionary {
+    let opts = match CloseOpts::parse_obj(with_action_defaults("close", opts).unwrap_or_default()) {
+        Ok(opts) => opts,
+        Err(e) => {
+            notify_error(&format!(
+                "[nvim_winpick] failed to parse 'close_opts': {e:#?}"
+            ));
+            return Dictionary::new();
+        }
     };
-    let mut ctx = Context::DEFAULT;
-    match pick::simple_operations::pick_close_window(&opts, &mut ctx) {
-        Ok(()) => {}
+    repeat::not_repeatable("close", opts.opts.dot_repeat);
+    let mut ctx = Context::new(&opts.opts);
+    match hooks::around("close", &opts.opts.hooks, || {
+        history::recorded(|| pick::close::pick_close_window(&opts, &mut ctx))
+    }) {
+        Ok(summary) => summary.into_dict(),
         Err(e) => {
             notify_error(&format!(
                 "[nvim_winpick] failed to pick window to close: {e:#?}"
             ));
+            Dictionary::new()
+        }
+    }
+}
+
+/// Closes every window matching the filter rules except the picked ones, returns the same
+/// summary as [`pick_close_window`]
+#[must_use]
+pub fn pick_only_windows(opts: Option<Object>) -> Dictionary {
+    let opts = match CloseOpts::parse_obj(with_action_defaults("only", opts).unwrap_or_default()) {
+        Ok(opts) => opts,
+        Err(e) => {
+            notify_error(&format!(
+                "[nvim_winpick] failed to parse 'close_opts': {e:#?}"
+            ));
+            return Dictionary::new();
+        }
+    };
+    repeat::not_repeatable("only", opts.opts.dot_repeat);
+    let mut ctx = Context::new(&opts.opts);
+    match hooks::around("only", &opts.opts.hooks, || {
+        history::recorded(|| pick::close::pick_only_windows(&opts, &mut ctx))
+    }) {
+        Ok(summary) => summary.into_dict(),
+        Err(e) => {
+            notify_error(&format!(
+                "[nvim_winpick] failed to pick windows to keep: {e:#?}"
+            ));
+            Dictionary::new()
         }
     }
 }
 
 pub fn pick_swap_window(opts: Option<Object>) {
-    let Some(opts) = safe_parse_opts(opts) else {
+    let Some(opts) = safe_parse_opts(with_action_defaults("swap", opts)) else {
         return;
     };
-    let mut ctx = Context::DEFAULT;
-    match pick::simple_operations::pick_swap_window(true, &opts, &mut ctx) {
+    let mut ctx = Context::new(&opts);
+    match hooks::around("swap", &opts.hooks, || {
+        history::recorded(|| pick::simple_operations::pick_swap_window(true, &opts, &mut ctx))
+    }) {
         Ok(()) => {}
         Err(e) => {
             notify_error(&format!(
@@ -117,12 +220,54 @@ pub fn pick_swap_window(opts: Option<Object>) {
     }
 }
 
+pub fn pick_stash_window(opts: Option<Object>) {
+    let Some(opts) = safe_parse_opts(with_action_defaults("stash", opts)) else {
+        return;
+    };
+    repeat::not_repeatable("stash", opts.dot_repeat);
+    let mut ctx = Context::new(&opts);
+    if let Err(e) = hooks::around("stash", &opts.hooks, || {
+        history::recorded(|| pick::stash::pick_stash_window(&opts, &mut ctx))
+    }) {
+        notify_error(&format!(
+            "[nvim_winpick] failed to pick window to stash: {e:#?}"
+        ));
+    }
+}
+
+pub fn unstash(opts: Option<Object>) {
+    let opts =
+        match UnstashOpts::parse_obj(with_action_defaults("unstash", opts).unwrap_or_default()) {
+            Ok(opts) => opts,
+            Err(e) => {
+                notify_error(&format!(
+                    "[nvim_winpick] failed to parse 'unstash_opts': {e:#?}"
+                ));
+                return;
+            }
+        };
+    // Unstash has no opts of its own to take hooks from
+    let hooks = Opts::default().hooks;
+    if let Err(e) = hooks::around("unstash", &hooks, || {
+        history::recorded(|| pick::stash::unstash(opts.pick, opts.focus_new, &hooks))
+    }) {
+        notify_error(&format!("[nvim_winpick] failed to unstash window: {e:#?}"));
+    }
+}
+
 pub fn open_split(opts: Option<Object>) {
+    open_split_as(opts, |vertical| SplitKind::Default { vertical });
+}
+
+/// [`open_split`], with `kind` deciding where the split goes from the `vertical` opt
+pub(crate) fn open_split_as(opts: Option<Object>, kind: impl FnOnce(bool) -> SplitKind) {
     let Some(opts) = opts else {
-        notify_error("[nvim_winpick] failed to open split, no opts supplied, needs at least { path = <path> }");
+        notify_error("[nvim_winpick] failed to open split, no opts supplied, needs at least { path = <path> } or { buf = <bufnr> }");
         return;
     };
-    let opts = match OpenSplitOpts::parse_obj(opts) {
+    let opts = match OpenSplitOpts::parse_obj(
+        with_action_defaults("split", Some(opts)).unwrap_or_default(),
+    ) {
         Ok(opts) => opts,
         Err(e) => {
             notify_error(&format!(
@@ -131,14 +276,21 @@ pub fn open_split(opts: Option<Object>) {
             return;
         }
     };
-    let mut ctx = Context::DEFAULT;
-    if let Err(e) = pick::simple_open::open_simple_split_at_win(
-        opts.focus_new,
-        opts.vertical,
-        &opts.path,
-        &opts.opts,
-        &mut ctx,
-    ) {
+    let kind = kind(opts.vertical);
+    let mut ctx = Context::new(&opts.opts);
+    if let Err(e) = hooks::around("split", &opts.opts.hooks, || {
+        history::recorded(|| {
+            pick::simple_open::open_simple_split_at_win(
+                opts.focus_new,
+                kind,
+                &opts.source,
+                opts.location,
+                opts.sizing,
+                &opts.opts,
+                &mut ctx,
+            )
+        })
+    }) {
         notify_error(&format!("[nvim_winpick] failed to open split: {e:#?}"));
     }
 }
@@ -146,10 +298,12 @@ pub fn open_split(opts: Option<Object>) {
 pub fn open_over(opts: Option<Object>) {
     {
         let Some(opts) = opts else {
-            notify_error("[nvim_winpick] failed to open over, no opts supplied, needs at least { path = <path> }");
+            notify_error("[nvim_winpick] failed to open over, no opts supplied, needs at least { path = <path> } or { buf = <bufnr> }");
             return;
         };
-        let opts = match OpenOverOpts::parse_obj(opts) {
+        let opts = match OpenOverOpts::parse_obj(
+            with_action_defaults("over", Some(opts)).unwrap_or_default(),
+        ) {
             Ok(opts) => opts,
             Err(e) => {
                 notify_error(&format!(
@@ -158,10 +312,18 @@ pub fn open_over(opts: Option<Object>) {
                 return;
             }
         };
-        let mut ctx = Context::DEFAULT;
-        if let Err(e) =
-            pick::simple_open::open_over_win(&opts.path, opts.focus_new, &opts.opts, &mut ctx)
-        {
+        let mut ctx = Context::new(&opts.opts);
+        if let Err(e) = hooks::around("over", &opts.opts.hooks, || {
+            history::recorded(|| {
+                pick::simple_open::open_over_win(
+                    &opts.source,
+                    opts.location,
+                    opts.focus_new,
+                    &opts.opts,
+                    &mut ctx,
+                )
+            })
+        }) {
             notify_error(&format!("[nvim_winpick] failed to open over: {e:#?}"));
         }
     }
@@ -170,27 +332,230 @@ pub fn open_over(opts: Option<Object>) {
 pub fn pick_win_relative(opts: Option<Object>) {
     let Some(opts) = opts else {
         notify_error(
-            "[nvim_winpick] failed to pick_win_relative, no opts supplied needs at least { path = <path> }"
+            "[nvim_winpick] failed to pick_win_relative, no opts supplied needs at least { path = <path> } or { buf = <bufnr> }"
         );
         return;
     };
-    let opts = match OpenRelativeOpts::parse_obj(opts) {
+    let opts = match OpenRelativeOpts::parse_obj(
+        with_action_defaults("relative", Some(opts)).unwrap_or_default(),
+    ) {
         Ok(opts) => opts,
         Err(e) => {
             notify_error(&format!(
-                "[nvim_winpick] failed to parse 'open_over_opts': {e:#?}"
+                "[nvim_winpick] failed to parse 'open_relative_opts': {e:#?}"
             ));
             return;
         }
     };
-    let mut ctx = Context::DEFAULT;
-    if let Err(e) = pick::win_relative::pick_win_relative(
-        &opts.path,
-        opts.focus_new,
-        &opts.relative_chars,
-        &opts.opts,
-        &mut ctx,
+    let mut ctx = Context::new(&opts.opts);
+    if let Err(e) = hooks::around("relative", &opts.opts.hooks, || {
+        history::recorded(|| {
+            pick::win_relative::pick_win_relative(
+                &opts.source,
+                opts.location,
+                opts.sizing,
+                opts.focus_new,
+                &opts.relative_keys,
+                &opts.opts,
+                &mut ctx,
+            )
+        })
+    }) {
+        notify_error(&format!("[nvim_winpick] failed to open relative: {e:#?}"));
+    }
+}
+
+pub fn place_window_relative(opts: Option<Object>) {
+    let opts =
+        match PlaceRelativeOpts::parse_obj(with_action_defaults("place", opts).unwrap_or_default())
+        {
+            Ok(opts) => opts,
+            Err(e) => {
+                notify_error(&format!(
+                    "[nvim_winpick] failed to parse 'place_relative_opts': {e:#?}"
+                ));
+                return;
+            }
+        };
+    let mut ctx = Context::new(&opts.opts);
+    if let Err(e) = hooks::around("place", &opts.opts.hooks, || {
+        history::recorded(|| {
+            pick::win_relative::pick_place_window_relative(
+                opts.source_win,
+                opts.target_win,
+                opts.position,
+                opts.focus_new,
+                &opts.relative_keys,
+                &opts.opts,
+                &mut ctx,
+            )
+        })
+    }) {
+        notify_error(&format!(
+            "[nvim_winpick] failed to place window relative: {e:#?}"
+        ));
+    }
+}
+
+pub fn pick_open_many(opts: Option<Object>) {
+    let Some(opts) = opts else {
+        notify_error("[nvim_winpick] failed to open many, no opts supplied, needs at least { paths = { <path> } }");
+        return;
+    };
+    let opts = match OpenManyOpts::parse_obj(
+        with_action_defaults("open_many", Some(opts)).unwrap_or_default(),
     ) {
-        notify_error(&format!("[nvim_winpick] failed to open over: {e:#?}"));
+        Ok(opts) => opts,
+        Err(e) => {
+            notify_error(&format!(
+                "[nvim_winpick] failed to parse 'open_many_opts': {e:#?}"
+            ));
+            return;
+        }
+    };
+    repeat::not_repeatable("open_many", opts.opts.dot_repeat);
+    let mut ctx = Context::new(&opts.opts);
+    if let Err(e) = hooks::around("open_many", &opts.opts.hooks, || {
+        history::recorded(|| {
+            pick::open_many::pick_open_many(
+                &opts.paths,
+                opts.sizing,
+                opts.focus_new,
+                &opts.relative_keys,
+                &opts.opts,
+                &mut ctx,
+            )
+        })
+    }) {
+        notify_error(&format!("[nvim_winpick] failed to open many: {e:#?}"));
+    }
+}
+
+pub fn pick_float_window(opts: Option<Object>) {
+    let Some(opts) = safe_parse_opts(with_action_defaults("float", opts)) else {
+        return;
+    };
+    repeat::not_repeatable("float", opts.dot_repeat);
+    let mut ctx = Context::new(&opts);
+    if let Err(e) = hooks::around("float", &opts.hooks, || {
+        history::recorded(|| pick::float::pick_float_window(&opts, &mut ctx))
+    }) {
+        notify_error(&format!(
+            "[nvim_winpick] failed to pick window to float: {e:#?}"
+        ));
+    }
+}
+
+pub fn pick_dock_float(opts: Option<Object>) {
+    let opts =
+        match DockFloatOpts::parse_obj(with_action_defaults("dock", opts).unwrap_or_default()) {
+            Ok(opts) => opts,
+            Err(e) => {
+                notify_error(&format!(
+                    "[nvim_winpick] failed to parse 'dock_float_opts': {e:#?}"
+                ));
+                return;
+            }
+        };
+    repeat::not_repeatable("dock", opts.opts.dot_repeat);
+    let mut ctx = Context::new(&opts.opts);
+    if let Err(e) = hooks::around("dock", &opts.opts.hooks, || {
+        history::recorded(|| {
+            pick::float::pick_dock_float(opts.focus_new, &opts.relative_keys, &opts.opts, &mut ctx)
+        })
+    }) {
+        notify_error(&format!("[nvim_winpick] failed to dock float: {e:#?}"));
+    }
+}
+
+pub fn pick_move_to_tab(opts: Option<Object>) {
+    let opts = match MoveToTabOpts::parse_obj(with_action_defaults("tab", opts).unwrap_or_default())
+    {
+        Ok(opts) => opts,
+        Err(e) => {
+            notify_error(&format!(
+                "[nvim_winpick] failed to parse 'move_to_tab_opts': {e:#?}"
+            ));
+            return;
+        }
+    };
+    repeat::not_repeatable("tab", opts.opts.dot_repeat);
+    let mut ctx = Context::new(&opts.opts);
+    if let Err(e) = hooks::around("tab", &opts.opts.hooks, || {
+        history::recorded(|| {
+            pick::tab::pick_move_to_tab(
+                opts.focus_new,
+                opts.relative.then_some(&opts.relative_keys),
+                &opts.opts,
+                &mut ctx,
+            )
+        })
+    }) {
+        notify_error(&format!(
+            "[nvim_winpick] failed to move window to tab: {e:#?}"
+        ));
+    }
+}
+
+/// Restores the current tabpage to how it was before the last window operation
+pub fn winpick_undo() {
+    if let Err(e) = hooks::around("undo", &Opts::default().hooks, history::undo) {
+        notify_error(&format!("[nvim_winpick] failed to undo: {e:#?}"));
+    }
+}
+
+/// Reapplies the last undone window operation of the current tabpage
+pub fn winpick_redo() {
+    if let Err(e) = hooks::around("redo", &Opts::default().hooks, history::redo) {
+        notify_error(&format!("[nvim_winpick] failed to redo: {e:#?}"));
+    }
+}
+
+/// Runs the last focus, swap, open or place again with the same picks, optionally opening
+/// `{ path = <path> }` or `{ buf = <bufnr> }` instead
+pub fn repeat_last(opts: Option<Object>) {
+    let opts = match RepeatOpts::parse_obj(opts.unwrap_or_default()) {
+        Ok(opts) => opts,
+        Err(e) => {
+            notify_error(&format!(
+                "[nvim_winpick] failed to parse 'repeat_opts': {e:#?}"
+            ));
+            return;
+        }
+    };
+    if let Err(e) = hooks::around("repeat", &Opts::default().hooks, || {
+        history::recorded(|| repeat::repeat_last(opts.source))
+    }) {
+        notify_error(&format!(
+            "[nvim_winpick] failed to repeat last action: {e:#?}"
+        ));
+    }
+}
+
+/// The `operatorfunc` of the `g@_` queued after an action with `dot_repeat` set, which makes
+/// `.` call [`repeat_operator`]
+pub fn prime_repeat() {
+    if let Err(e) = repeat::prime_repeat() {
+        notify_error(&format!(
+            "[nvim_winpick] failed to make action repeatable: {e:#?}"
+        ));
+    }
+}
+
+/// Waits for the key of an action and runs it, see `<Plug>(winpick-operator)`
+pub fn pick_operator() {
+    if let Err(e) = operator::pick_operator() {
+        notify_error(&format!("[nvim_winpick] failed to run operator: {e:#?}"));
+    }
+}
+
+/// The `operatorfunc` that makes `.` repeat the last action when `dot_repeat` is set
+pub fn repeat_operator() {
+    if let Err(e) = hooks::around("repeat", &Opts::default().hooks, || {
+        history::recorded(repeat::repeat_operator)
+    }) {
+        notify_error(&format!(
+            "[nvim_winpick] failed to repeat last action: {e:#?}"
+        ));
     }
 }
@@ -0,0 +1,29 @@
+use anyhow::{bail, Context as _};
+
+use crate::keymap::operator_targets;
+use crate::pick::ESC;
+
+/// Run by `<Plug>(winpick-operator)`, waits for a key and runs the action whose default mapping
+/// ends with it. The action runs with the count the operator was given, so its first pick takes
+/// the window with that label position without drawing, `2<Plug>(winpick-operator)c` closes the
+/// second labelled window
+pub(crate) fn pick_operator() -> anyhow::Result<()> {
+    let ch: u32 = nvim_oxi::api::call_function("getchar", ((),)).context("failed to get char")?;
+    if ch == ESC {
+        return Ok(());
+    }
+    let key = char::from_u32(ch);
+    let Some((_, _, action)) = operator_targets().find(|(target, _, _)| Some(*target) == key)
+    else {
+        let known: Vec<String> = operator_targets()
+            .map(|(key, name, _)| format!("{key} ({name})"))
+            .collect();
+        bail!(
+            "no action on '{}', expected one of [{}]",
+            key.map_or_else(|| ch.to_string(), String::from),
+            known.join(", ")
+        );
+    };
+    action();
+    Ok(())
+}
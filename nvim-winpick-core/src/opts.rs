@@ -1,12 +1,43 @@
-use std::sync::OnceLock;
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::sync::{Mutex, MutexGuard, PoisonError};
 
 use anyhow::{bail, Context};
+use nvim_oxi::api::{Buffer, Window};
 use nvim_oxi::conversion::FromObject;
-use nvim_oxi::{Array, Dictionary, Object};
+use nvim_oxi::{Array, Dictionary, Object, ObjectKind};
+use schemars::{json_schema, JsonSchema, Schema, SchemaGenerator};
+use serde::de::value::MapAccessDeserializer;
+use serde::de::{Error as _, IgnoredAny, MapAccess, SeqAccess, Unexpected, Visitor};
+use serde::{Deserialize, Deserializer};
 
+use crate::actions::ActionDefaults;
+use crate::buf::BufSource;
+use crate::geometry::Positions;
 use crate::hint::Hint;
+use crate::hooks::{Hook, Hooks};
+use crate::keymap::Keymaps;
+use crate::pick::close::{DeleteBuffer, ModifiedAction};
+use crate::validate::{self, Issues};
+use crate::win::{Location, SplitSize, SplitSizing};
+
+/// What `setup` was last called with, `setup` can be called again to replace it
+struct Setup {
+    opts: Option<Opts>,
+    profiles: Vec<(String, Opts)>,
+}
+
+static SETUP: Mutex<Setup> = Mutex::new(Setup {
+    opts: None,
+    profiles: Vec::new(),
+});
+
+fn setup_state() -> MutexGuard<'static, Setup> {
+    // Opts are replaced whole, a panic while holding the lock can't leave them half-written
+    SETUP.lock().unwrap_or_else(PoisonError::into_inner)
+}
 
-static SETUP_OPTS: OnceLock<Opts> = OnceLock::new();
 #[derive(Debug, Clone)]
 #[cfg_attr(feature = "test", derive(Eq, PartialEq))]
 pub struct Opts {
@@ -14,96 +45,216 @@ pub struct Opts {
     pub selection_chars: String,
     pub hint: Hint,
     pub multiselect: Option<Multiselect>,
+    pub relative_preview: bool,
+    pub dot_repeat: bool,
+    pub hooks: Hooks,
+    /// Picks the window with this (1-based) label position instead of drawing, per call only
+    pub count: Option<usize>,
 }
 
 impl Opts {
-    pub(crate) fn setup_opts(opts: Self) -> anyhow::Result<()> {
-        SETUP_OPTS
-            .set(opts)
-            .map_err(|_| anyhow::anyhow!("tried to setup twice"))
+    /// Merges `object` over the built-in defaults and makes it the default for every action,
+    /// along with its `profiles`, which are merged over the result in turn, and its per-action
+    /// defaults in `actions`. Returns the `keymaps` to bind
+    pub(crate) fn setup(object: Object) -> anyhow::Result<Keymaps> {
+        let dict = obj_to_dict(object)
+            .context("invalid 'setup opts'")?
+            .unwrap_or_default();
+        // Unknown keys go with the fields they're closest to, to be told what they were meant to
+        // be, the general fields by default
+        let setup_names = validate::field_names::<SetupFields>();
+        let general_names = validate::field_names::<OptsFields>();
+        let (setup, general): (Dictionary, Dictionary) = dict.into_iter().partition(|(key, _)| {
+            let key = key.to_string_lossy();
+            setup_names.iter().any(|name| *name == key)
+                || !general_names.iter().any(|name| *name == key)
+                    && validate::closest(&key, setup_names.iter().map(String::as_str)).is_some()
+        });
+        let mut issues = Issues::new("setup opts");
+        let opts = Self::parse_over(general.into(), Self::builtin(), &mut issues, "");
+        let setup = issues
+            .deserialize_rest::<SetupFields>(setup.into(), "")
+            .unwrap_or_default();
+        // Checked over the built-in defaults if the opts are invalid, to report everything
+        let base = opts.clone().unwrap_or_else(Self::builtin);
+        let mut profiles = Vec::new();
+        for (name, fields) in setup.profiles.unwrap_or_default() {
+            let path = format!("profiles.{name}");
+            fields.check_per_call(&mut issues, &path);
+            match Self::over(fields, base.clone()) {
+                Ok(profile) => profiles.push((name, profile)),
+                Err(e) => issues.push(path, format!("{e:#}")),
+            }
+        }
+        let actions = setup
+            .actions
+            .map(|actions| actions.check(&base, &mut issues))
+            .unwrap_or_default();
+        issues.into_result()?;
+        *setup_state() = Setup {
+            opts: Some(base),
+            profiles,
+        };
+        crate::actions::set_action_defaults(actions);
+        Ok(setup.keymaps.unwrap_or_default())
     }
+
+    /// A table is merged over the setup opts, a string names a profile from `setup`
     pub(crate) fn parse_obj(object: Object) -> anyhow::Result<Self> {
-        let dict = obj_to_dict(object).context("not a valid table")?;
-        let Some(dict) = dict else {
-            return Ok(Self::default());
-        };
-        let mut filter_rules = None;
-        let mut selection_chars = None;
-        let mut hint = None;
-        let mut multiselect = None;
-        for (tag, obj) in dict {
-            let str_tag = tag
-                .to_str()
-                .context("unexpected non-utf-8 field in opts table")?;
-            match str_tag {
-                "filter_rules" => {
-                    if filter_rules.is_some() {
-                        bail!("'filter_rules' supplied more than once");
-                    }
-                    let fr = FilterRules::parse_obj(obj).context("invalid 'filter_rules'")?;
-                    filter_rules = Some(fr);
-                }
-                "selection_chars" => {
-                    if selection_chars.is_some() {
-                        bail!("'selection_chars' supplied more than once");
-                    }
-                    let sel = String::from_object(obj).context("invalid 'selection_chars'")?;
-                    selection_chars = Some(sel);
-                }
-                "hint" => {
-                    if hint.is_some() {
-                        bail!("'hint' supplied more than once");
-                    }
-                    let sel_hint = String::from_object(obj).context("invalid 'hint'")?;
-                    let parsed = Hint::from_str(&sel_hint)?;
-                    hint = Some(parsed);
-                }
-                "multiselect" => {
-                    if multiselect.is_some() {
-                        bail!("'multi_select' supplied more than once");
-                    }
-                    let ms = Multiselect::parse_obj(obj)?;
-                    multiselect = ms;
-                }
+        if object.kind() == ObjectKind::String {
+            let name = String::from_object(object).context("invalid profile name")?;
+            return Self::profile(&name);
+        }
+        let fields: OptsFields = validate::deserialize(object, "opts")?;
+        Self::over(fields, Self::default())
+    }
 
-                unk => {
-                    bail!("member '{unk}', not recognized");
-                }
+    /// Merges `object`, found at `path`, over `base`, recording what's wrong with it in `issues`
+    pub(crate) fn parse_over(
+        object: Object,
+        base: Self,
+        issues: &mut Issues,
+        path: &str,
+    ) -> Option<Self> {
+        let fields = issues.deserialize::<OptsFields>(object, path)?;
+        fields.check_per_call(issues, path);
+        match Self::over(fields, base) {
+            Ok(opts) => Some(opts),
+            Err(e) => {
+                issues.push(path, format!("{e:#}"));
+                None
             }
         }
-        let hint = hint.unwrap_or_default();
-        let selection_chars = if let Some(sel) = selection_chars {
-            validate_provided_selection_chars(&sel, hint)?;
-            sel
-        } else {
-            default_selection_chars()
+    }
+
+    fn profile(name: &str) -> anyhow::Result<Self> {
+        let setup = setup_state();
+        let Some((_, profile)) = setup.profiles.iter().find(|(profile, _)| profile == name) else {
+            let known: Vec<&str> = setup
+                .profiles
+                .iter()
+                .map(|(name, _)| name.as_str())
+                .collect();
+            bail!(
+                "no profile named '{name}', known profiles: [{}]",
+                known.join(", ")
+            );
+        };
+        Ok(profile.clone())
+    }
+
+    /// Fields set in `fields` replace those of `base`, nested tables are merged the same way,
+    /// lists are replaced whole
+    fn over(fields: OptsFields, base: Self) -> anyhow::Result<Self> {
+        // Changing the hint can make the inherited chars unrenderable
+        let validate_chars = fields.selection_chars.is_some() || fields.hint.is_some();
+        let hint = fields.hint.unwrap_or(base.hint);
+        let selection_chars = fields.selection_chars.unwrap_or(base.selection_chars);
+        if validate_chars {
+            validate_provided_selection_chars(&selection_chars, hint)?;
+        }
+        let multiselect = match fields.multiselect {
+            Some(multiselect) => multiselect.parse()?,
+            None => base.multiselect,
+        };
+        let filter_rules = match fields.filter_rules {
+            Some(filter_rules) => filter_rules.over(base.filter_rules),
+            None => base.filter_rules,
         };
         Self {
-            filter_rules: filter_rules.unwrap_or_default(),
+            filter_rules,
             selection_chars,
             hint,
             multiselect,
+            relative_preview: fields.relative_preview.unwrap_or(base.relative_preview),
+            dot_repeat: fields.dot_repeat.unwrap_or(base.dot_repeat),
+            hooks: match fields.hooks {
+                Some(hooks) => hooks.over(base.hooks),
+                None => base.hooks,
+            },
+            // Not inherited, the setup opts and profiles can't have one, 0 is no count like `v:count`
+            count: fields.count.filter(|count| *count > 0),
         }
         .validate()
     }
 
     pub(crate) fn validate(self) -> anyhow::Result<Self> {
-        if let Some(ms) = self.multiselect {
-            if self.selection_chars.contains(ms.commit_char) {
-                bail!(
-                    "invalid, 'multiselect' and 'selection_chars' overlap with commit_char={}",
-                    ms.commit_char
-                )
-            }
-            if self.selection_chars.contains(ms.trigger_char) {
-                bail!(
-                    "invalid, 'multiselect' and 'selection_chars' overlap with trigger_char={}",
-                    ms.trigger_char
-                )
-            }
+        if let Some((name, ch)) = self.multiselect_overlap() {
+            bail!("invalid, 'multiselect' and 'selection_chars' overlap with {name}={ch}");
         }
         Ok(self)
     }
+
+    /// The `multiselect` char that's also a selection char, with the name of its field
+    pub(crate) fn multiselect_overlap(&self) -> Option<(&'static str, char)> {
+        let ms = self.multiselect?;
+        [
+            ("commit_char", ms.commit_char),
+            ("trigger_char", ms.trigger_char),
+        ]
+        .into_iter()
+        .find(|(_, ch)| self.selection_chars.contains(*ch))
+    }
+}
+
+/// The fields of an opts table as given, unset fields are taken from the base it's merged over
+#[derive(Default, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+#[schemars(rename = "Opts")]
+pub(crate) struct OptsFields {
+    filter_rules: Option<FilterRulesFields>,
+    selection_chars: Option<String>,
+    hint: Option<Hint>,
+    multiselect: Option<MultiselectFields>,
+    relative_preview: Option<bool>,
+    dot_repeat: Option<bool>,
+    hooks: Option<HooksFields>,
+    count: Option<usize>,
+}
+
+impl OptsFields {
+    /// A count given as a default would make every pick skip the labels
+    fn check_per_call(&self, issues: &mut Issues, path: &str) {
+        if self.count.is_some() {
+            issues.push(
+                validate::key_path(path, "count"),
+                "only taken per call, not as a default",
+            );
+        }
+    }
+}
+
+/// The keys `setup` takes next to the fields of [`Opts`]
+#[derive(Default, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct SetupFields {
+    profiles: Option<BTreeMap<String, OptsFields>>,
+    actions: Option<ActionDefaults>,
+    keymaps: Option<Keymaps>,
+}
+
+// The prefix is part of the lua names
+#[allow(clippy::struct_field_names)]
+#[derive(Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+#[schemars(rename = "Hooks")]
+pub(crate) struct HooksFields {
+    on_pick_start: Option<Hook>,
+    on_pick: Option<Hook>,
+    on_cancel: Option<Hook>,
+    on_action_done: Option<Hook>,
+}
+
+impl HooksFields {
+    /// Hooks that are set replace those of `base`
+    fn over(self, base: Hooks) -> Hooks {
+        Hooks {
+            on_pick_start: self.on_pick_start.or(base.on_pick_start),
+            on_pick: self.on_pick.or(base.on_pick),
+            on_cancel: self.on_cancel.or(base.on_cancel),
+            on_action_done: self.on_action_done.or(base.on_action_done),
+        }
+    }
 }
 
 // I know, I know 'parse don't validate', but I don't want to make more structs for this right now
@@ -131,16 +282,34 @@ fn validate_provided_selection_chars(chars: &str, hint: Hint) -> anyhow::Result<
     Ok(())
 }
 
+/// The opts and profiles from `setup`, if it ran
+pub(crate) fn setup_opts() -> Option<(Opts, Vec<(String, Opts)>)> {
+    let setup = setup_state();
+    let opts = setup.opts.clone()?;
+    Some((opts, setup.profiles.clone()))
+}
+
 impl Default for Opts {
     fn default() -> Self {
-        if let Some(global) = SETUP_OPTS.get() {
+        if let Some(global) = &setup_state().opts {
             return global.clone();
         }
+        Self::builtin()
+    }
+}
+
+impl Opts {
+    /// The defaults before any `setup`
+    fn builtin() -> Self {
         Self {
             filter_rules: FilterRules::default(),
             selection_chars: default_selection_chars(),
             hint: Hint::default(),
             multiselect: None,
+            relative_preview: false,
+            dot_repeat: false,
+            hooks: Hooks::default(),
+            count: None,
         }
     }
 }
@@ -162,73 +331,36 @@ pub struct FilterRules {
     pub file_name_contains: Vec<String>,
 }
 
-macro_rules! parse_from_obj_with_err {
-    ($dest: ident, $source: expr, $kind: ty) => {
-        if $dest.is_some() {
-            anyhow::bail!("'{}' supplied more than once", stringify!($dest));
-        }
-        $dest =
-            Some(<$kind>::from_object($source).with_context(|| {
-                format!("'{}' not a {}", stringify!($dest), stringify!($kind),)
-            })?);
-    };
+#[derive(Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+#[schemars(rename = "FilterRules")]
+pub(crate) struct FilterRulesFields {
+    autoselect_one: Option<bool>,
+    include_current_win: Option<bool>,
+    include_floating: Option<bool>,
+    include_unfocusable_windows: Option<bool>,
+    bo: Option<BoFields>,
+    file_path_contains: Option<Vec<String>>,
+    file_name_contains: Option<Vec<String>>,
 }
 
-impl FilterRules {
-    pub(crate) fn parse_obj(object: Object) -> anyhow::Result<Self> {
-        let dict = obj_to_dict(object).context("invalid table")?;
-        let Some(dict) = dict else {
-            return Ok(Self::default());
-        };
-        let mut autoselect_one = None;
-        let mut include_current_win = None;
-        let mut include_floating = None;
-        let mut include_unfocusable_windows = None;
-        let mut bo = None;
-        let mut file_path_contains = None;
-        let mut file_name_contains = None;
-        for (tag, obj) in dict {
-            let str_tag = tag
-                .to_str()
-                .context("unexpected non-utf-8 field in filter_rules")?;
-            match str_tag {
-                "autoselect_one" => {
-                    parse_from_obj_with_err!(autoselect_one, obj, bool);
-                }
-                "include_current_win" => {
-                    parse_from_obj_with_err!(include_current_win, obj, bool);
-                }
-                "include_floating" => {
-                    parse_from_obj_with_err!(include_floating, obj, bool);
-                }
-                "include_unfocusable_windows" => {
-                    parse_from_obj_with_err!(include_unfocusable_windows, obj, bool);
-                }
-                "bo" => {
-                    if bo.is_some() {
-                        bail!("'bo' supplied more than once")
-                    }
-                    bo = Some(Bo::parse_obj(obj)?);
-                }
-                "file_path_contains" => {
-                    parse_from_obj_with_err!(file_path_contains, obj, Vec<String>);
-                }
-                "file_name_contains" => {
-                    parse_from_obj_with_err!(file_name_contains, obj, Vec<String>);
-                }
-
-                unk => bail!("failed to parse 'filter_rules' member, '{unk}' not recognized"),
-            }
+impl FilterRulesFields {
+    /// Fields that are set replace those of `base`
+    fn over(self, base: FilterRules) -> FilterRules {
+        FilterRules {
+            autoselect_one: self.autoselect_one.unwrap_or(base.autoselect_one),
+            include_current_win: self.include_current_win.unwrap_or(base.include_current_win),
+            include_floating: self.include_floating.unwrap_or(base.include_floating),
+            include_unfocusable_windows: self
+                .include_unfocusable_windows
+                .unwrap_or(base.include_unfocusable_windows),
+            bo: match self.bo {
+                Some(bo) => bo.over(base.bo),
+                None => base.bo,
+            },
+            file_path_contains: self.file_path_contains.unwrap_or(base.file_path_contains),
+            file_name_contains: self.file_name_contains.unwrap_or(base.file_name_contains),
         }
-        Ok(Self {
-            autoselect_one: autoselect_one.unwrap_or_else(default_true),
-            include_current_win: include_current_win.unwrap_or_else(default_true),
-            include_floating: include_floating.unwrap_or_default(),
-            include_unfocusable_windows: include_unfocusable_windows.unwrap_or_default(),
-            bo: bo.unwrap_or_default(),
-            file_path_contains: file_path_contains.unwrap_or_default(),
-            file_name_contains: file_name_contains.unwrap_or_default(),
-        })
     }
 }
 
@@ -246,10 +378,10 @@ impl Default for FilterRules {
     }
 }
 
-fn obj_to_dict(obj: Object) -> anyhow::Result<Option<Dictionary>> {
+pub(crate) fn obj_to_dict(obj: Object) -> anyhow::Result<Option<Dictionary>> {
     match obj.kind() {
-        nvim_oxi::ObjectKind::Nil => Ok(None),
-        nvim_oxi::ObjectKind::Array => {
+        ObjectKind::Nil => Ok(None),
+        ObjectKind::Array => {
             let arr = Array::from_object(obj)
                 .context("Bug, failed to convert checked object to an array")?;
             if !arr.is_empty() {
@@ -258,7 +390,7 @@ fn obj_to_dict(obj: Object) -> anyhow::Result<Option<Dictionary>> {
             // An empty array is the same as a nil-dict
             Ok(None)
         }
-        nvim_oxi::ObjectKind::Dictionary => Ok(Some(
+        ObjectKind::Dictionary => Ok(Some(
             Dictionary::from_object(obj).context("failed to convert checked object to a dict")?,
         )),
         t => bail!("unexpect object kind {t:?}"),
@@ -272,51 +404,22 @@ pub struct Multiselect {
     pub commit_char: char,
 }
 
-impl Multiselect {
-    pub(crate) fn parse_obj(object: Object) -> anyhow::Result<Option<Self>> {
-        let dict = obj_to_dict(object).context("invalid table")?;
-        let Some(dict) = dict else {
-            return Ok(None);
-        };
-        let mut trigger_char = None;
-        let mut commit_char = None;
-        for (tag, obj) in dict {
-            let str_tag = tag
-                .to_str()
-                .context("unexpected non-utf-8 field in multiselect")?;
-            match str_tag {
-                "trigger_char" => {
-                    parse_from_obj_with_err!(trigger_char, obj, String);
-                }
-                "commit_char" => {
-                    parse_from_obj_with_err!(commit_char, obj, String);
-                }
-                unk => bail!("failed to parse 'multiselect' member, '{unk}' not recognized"),
-            }
-        }
-        match (trigger_char, commit_char) {
-            (Some(trigger), Some(commit)) => {
-                let lc_trigger = trigger.to_lowercase();
-                let mut lc_trigger_chars = lc_trigger.chars();
-                let trigger_char = lc_trigger_chars.next().context(
-                    "invalid 'multiselect' configuration, no chars in 'trigger_char'-string",
-                )?;
-                if lc_trigger_chars.next().is_some() {
-                    bail!("invalid 'multiselect' configuration, too many chars in 'trigger_char' string ({trigger}), only one char allowed");
-                }
-                let lc_commit = commit.to_lowercase();
-                let mut lc_commit_chars = lc_commit.chars();
-                let commit_char = lc_commit_chars.next().context(
-                    "invalid 'multiselect' configuration, no chars in 'commit_char'-string",
-                )?;
-                if lc_commit_chars.next().is_some() {
-                    bail!("invalid 'multiselect' configuration, too many chars in 'commit_char' string ('{commit}'), only one char allowed");
-                }
-                Ok(Some(Self {
-                    trigger_char,
-                    commit_char,
-                }))
-            }
+#[derive(Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+#[schemars(rename = "Multiselect")]
+pub(crate) struct MultiselectFields {
+    trigger_char: Option<char>,
+    commit_char: Option<char>,
+}
+
+impl MultiselectFields {
+    /// Both chars turn multiselect on, an empty table turns it off
+    fn parse(self) -> anyhow::Result<Option<Multiselect>> {
+        match (self.trigger_char, self.commit_char) {
+            (Some(trigger), Some(commit)) => Ok(Some(Multiselect {
+                trigger_char: lowercase_char(trigger, "trigger_char")?,
+                commit_char: lowercase_char(commit, "commit_char")?,
+            })),
             (Some(_), None) | (None, Some(_)) => {
                 bail!("invalid 'multiselect' configuration, only one of 'trigger_char' and 'commit_char' was set, both or none need to be set" );
             }
@@ -325,6 +428,15 @@ impl Multiselect {
     }
 }
 
+/// Picks are matched case-insensitively, so the chars are kept lowercase
+fn lowercase_char(ch: char, name: &str) -> anyhow::Result<char> {
+    let mut lower = ch.to_lowercase();
+    match (lower.next(), lower.next()) {
+        (Some(lower), None) => Ok(lower),
+        _ => bail!("invalid 'multiselect' configuration, '{name}' = {ch} doesn't lowercase to a single char"),
+    }
+}
+
 #[inline]
 fn default_true() -> bool {
     true
@@ -347,30 +459,29 @@ impl Default for Bo {
 }
 
 impl Bo {
-    fn parse_obj(object: Object) -> anyhow::Result<Self> {
-        let dict = obj_to_dict(object).context("invalid table")?;
-        let Some(dict) = dict else {
-            return Ok(Self::default());
-        };
-        let mut filetype = None;
-        let mut buftype = None;
-        for (tag, obj) in dict {
-            let str_tag = tag.to_str().context("unexpected non-utf-8 field in bo")?;
-            match str_tag {
-                "filetype" => {
-                    parse_from_obj_with_err!(filetype, obj, Vec<String>);
-                }
-                "buftype" => {
-                    parse_from_obj_with_err!(buftype, obj, Vec<String>);
-                }
-                unk => bail!("failed to parse 'filter_rules' member, '{unk}' not recognized"),
-            }
+    fn empty() -> Self {
+        Self {
+            filetype: Vec::new(),
+            buftype: Vec::new(),
         }
+    }
+}
 
-        Ok(Self {
-            filetype: filetype.unwrap_or_default(),
-            buftype: buftype.unwrap_or_default(),
-        })
+#[derive(Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+#[schemars(rename = "Bo")]
+pub(crate) struct BoFields {
+    filetype: Option<Vec<String>>,
+    buftype: Option<Vec<String>>,
+}
+
+impl BoFields {
+    /// Fields that are set replace those of `base`
+    fn over(self, base: Bo) -> Bo {
+        Bo {
+            filetype: self.filetype.unwrap_or(base.filetype),
+            buftype: self.buftype.unwrap_or(base.buftype),
+        }
     }
 }
 
@@ -391,151 +502,691 @@ fn default_bts() -> Vec<String> {
     ]
 }
 
+/// Nested `opts`, a profile name or a table merged over the setup opts
+enum NestedOpts {
+    Profile(String),
+    Table(OptsFields),
+}
+
+impl<'de> Deserialize<'de> for NestedOpts {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct NestedVisitor;
+
+        impl<'de> Visitor<'de> for NestedVisitor {
+            type Value = NestedOpts;
+
+            fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str("a profile name or a table")
+            }
+
+            fn visit_str<E: serde::de::Error>(self, name: &str) -> Result<Self::Value, E> {
+                Ok(NestedOpts::Profile(name.to_string()))
+            }
+
+            fn visit_map<A: MapAccess<'de>>(self, map: A) -> Result<Self::Value, A::Error> {
+                OptsFields::deserialize(MapAccessDeserializer::new(map)).map(NestedOpts::Table)
+            }
+
+            // An empty lua table is an array
+            fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+                if seq.next_element::<IgnoredAny>()?.is_some() {
+                    return Err(A::Error::invalid_type(Unexpected::Seq, &self));
+                }
+                Ok(NestedOpts::Table(OptsFields::default()))
+            }
+        }
+
+        deserializer.deserialize_any(NestedVisitor)
+    }
+}
+
+impl JsonSchema for NestedOpts {
+    fn inline_schema() -> bool {
+        true
+    }
+
+    fn schema_name() -> Cow<'static, str> {
+        "NestedOpts".into()
+    }
+
+    fn json_schema(generator: &mut SchemaGenerator) -> Schema {
+        json_schema!({
+            "anyOf": [
+                { "type": "string" },
+                generator.subschema_for::<OptsFields>(),
+            ]
+        })
+    }
+}
+
+/// Nested `opts` name a profile, which only exists once `setup` ran, so they're resolved after
+/// the rest is parsed
+fn nested_opts(opts: Option<NestedOpts>) -> anyhow::Result<Opts> {
+    match opts {
+        None => Ok(Opts::default()),
+        Some(NestedOpts::Profile(name)) => Opts::profile(&name).context("invalid 'opts'"),
+        Some(NestedOpts::Table(fields)) => {
+            Opts::over(fields, Opts::default()).context("invalid 'opts'")
+        }
+    }
+}
+
 #[derive(Debug)]
 pub struct OpenSplitOpts {
-    pub(crate) path: String,
+    pub(crate) source: BufSource,
+    pub(crate) location: Option<Location>,
+    pub(crate) sizing: SplitSizing,
     pub(crate) focus_new: bool,
     pub(crate) vertical: bool,
     pub(crate) opts: Opts,
 }
 
+#[derive(Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+#[schemars(rename = "OpenSplitOpts")]
+pub(crate) struct OpenSplitFields {
+    path: Option<String>,
+    #[schemars(with = "Option<i32>")]
+    buf: Option<Buffer>,
+    lnum: Option<usize>,
+    col: Option<usize>,
+    end_lnum: Option<usize>,
+    #[serde(default, deserialize_with = "deserialize_split_size")]
+    size: Option<SplitSize>,
+    pick_size: Option<bool>,
+    focus_new: Option<bool>,
+    vertical: Option<bool>,
+    opts: Option<NestedOpts>,
+}
+
 impl OpenSplitOpts {
     pub fn parse_obj(object: Object) -> anyhow::Result<Self> {
-        let dict = obj_to_dict(object).context("invalid table")?;
-        let Some(dict) = dict else {
-            bail!("'open_split_opts' needs at least a path");
-        };
-        let mut path = None;
-        let mut focus_new = None;
-        let mut vertical = None;
-        let mut opts = None;
-        for (tag, obj) in dict {
-            let str_tag = tag
-                .to_str()
-                .context("unexpected non-utf-8 field in 'open_split_opts'")?;
-            match str_tag {
-                "path" => {
-                    parse_from_obj_with_err!(path, obj, String);
-                }
-                "focus_new" => {
-                    parse_from_obj_with_err!(focus_new, obj, bool);
-                }
-                "vertical" => {
-                    parse_from_obj_with_err!(vertical, obj, bool);
-                }
-                "opts" => {
-                    opts = Some(Opts::parse_obj(obj)?);
-                }
-                unk => bail!("failed to parse 'open_split_opts' member, '{unk}' not recognized"),
-            }
-        }
-
+        let fields: OpenSplitFields = validate::deserialize(object, "open_split_opts")?;
         Ok(Self {
-            path: path.context("'open_split_opts' needs 'path' to be set")?,
-            focus_new: focus_new.unwrap_or(true),
-            vertical: vertical.unwrap_or_default(),
-            opts: opts.unwrap_or_default(),
+            source: buf_source(fields.path, fields.buf, "open_split_opts")?,
+            location: location(fields.lnum, fields.col, fields.end_lnum, "open_split_opts")?,
+            sizing: SplitSizing {
+                size: fields.size,
+                prompt: fields.pick_size.unwrap_or_default(),
+            },
+            focus_new: fields.focus_new.unwrap_or(true),
+            vertical: fields.vertical.unwrap_or_default(),
+            opts: nested_opts(fields.opts)?,
         })
     }
 }
 
 #[derive(Debug)]
 pub struct OpenOverOpts {
-    pub(crate) path: String,
+    pub(crate) source: BufSource,
+    pub(crate) location: Option<Location>,
     pub(crate) focus_new: bool,
     pub(crate) opts: Opts,
 }
 
+#[derive(Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+#[schemars(rename = "OpenOverOpts")]
+pub(crate) struct OpenOverFields {
+    path: Option<String>,
+    #[schemars(with = "Option<i32>")]
+    buf: Option<Buffer>,
+    lnum: Option<usize>,
+    col: Option<usize>,
+    end_lnum: Option<usize>,
+    opts: Option<NestedOpts>,
+    focus_new: Option<bool>,
+}
+
 impl OpenOverOpts {
     pub fn parse_obj(object: Object) -> anyhow::Result<Self> {
-        let dict = obj_to_dict(object).context("invalid table")?;
-        let Some(dict) = dict else {
-            bail!("'open_over_opts' needs at least a path");
+        let fields: OpenOverFields = validate::deserialize(object, "open_over_opts")?;
+        Ok(Self {
+            source: buf_source(fields.path, fields.buf, "open_over_opts")?,
+            location: location(fields.lnum, fields.col, fields.end_lnum, "open_over_opts")?,
+            focus_new: fields.focus_new.unwrap_or_else(default_true),
+            opts: nested_opts(fields.opts)?,
+        })
+    }
+}
+
+pub struct OpenRelativeOpts {
+    pub(crate) source: BufSource,
+    pub(crate) location: Option<Location>,
+    pub(crate) sizing: SplitSizing,
+    pub(crate) focus_new: bool,
+    pub(crate) relative_keys: RelativeKeys,
+    pub(crate) opts: Opts,
+}
+
+#[derive(Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+#[schemars(rename = "OpenRelativeOpts")]
+pub(crate) struct OpenRelativeFields {
+    path: Option<String>,
+    #[schemars(with = "Option<i32>")]
+    buf: Option<Buffer>,
+    lnum: Option<usize>,
+    col: Option<usize>,
+    end_lnum: Option<usize>,
+    opts: Option<NestedOpts>,
+    relative_chars: Option<String>,
+    relative_keys: Option<RelativeKeys>,
+    relative_positions: Option<Vec<Positions>>,
+    #[serde(default, deserialize_with = "deserialize_split_size")]
+    size: Option<SplitSize>,
+    pick_size: Option<bool>,
+    focus_new: Option<bool>,
+}
+
+impl OpenRelativeOpts {
+    pub fn parse_obj(object: Object) -> anyhow::Result<Self> {
+        let fields: OpenRelativeFields = validate::deserialize(object, "open_relative_opts")?;
+        let opts = nested_opts(fields.opts)?;
+        let relative_keys = RelativeKeys::from_provided(
+            fields.relative_chars,
+            fields.relative_keys,
+            fields.relative_positions,
+            &opts,
+        )?;
+        // Don't need to check multiselect here, since it's not applicable for open relative
+        Ok(Self {
+            source: buf_source(fields.path, fields.buf, "open_relative_opts")?,
+            location: location(
+                fields.lnum,
+                fields.col,
+                fields.end_lnum,
+                "open_relative_opts",
+            )?,
+            sizing: SplitSizing {
+                size: fields.size,
+                prompt: fields.pick_size.unwrap_or_default(),
+            },
+            focus_new: fields.focus_new.unwrap_or_else(default_true),
+            relative_keys,
+            opts,
+        })
+    }
+}
+
+pub struct CloseOpts {
+    pub(crate) modified: ModifiedAction,
+    pub(crate) protect: Bo,
+    pub(crate) delete_buffer: DeleteBuffer,
+    pub(crate) opts: Opts,
+}
+
+#[derive(Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+#[schemars(rename = "CloseOpts")]
+pub(crate) struct CloseFields {
+    modified: Option<ModifiedAction>,
+    protect: Option<BoFields>,
+    delete_buffer: Option<DeleteBuffer>,
+    opts: Option<NestedOpts>,
+}
+
+impl CloseOpts {
+    pub fn parse_obj(object: Object) -> anyhow::Result<Self> {
+        let fields: CloseFields = validate::deserialize(object, "close_opts")?;
+        Ok(Self {
+            modified: fields.modified.unwrap_or_default(),
+            // Nothing is protected by default, unlike the filter rules
+            protect: fields
+                .protect
+                .map_or_else(Bo::empty, |protect| protect.over(Bo::empty())),
+            delete_buffer: fields.delete_buffer.unwrap_or_default(),
+            opts: nested_opts(fields.opts)?,
+        })
+    }
+}
+
+pub struct UnstashOpts {
+    pub(crate) pick: bool,
+    pub(crate) focus_new: bool,
+}
+
+#[derive(Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+#[schemars(rename = "UnstashOpts")]
+pub(crate) struct UnstashFields {
+    pick: Option<bool>,
+    focus_new: Option<bool>,
+}
+
+impl UnstashOpts {
+    pub fn parse_obj(object: Object) -> anyhow::Result<Self> {
+        let fields: UnstashFields = validate::deserialize(object, "unstash_opts")?;
+        Ok(Self {
+            pick: fields.pick.unwrap_or_default(),
+            focus_new: fields.focus_new.unwrap_or_else(default_true),
+        })
+    }
+}
+
+pub struct RepeatOpts {
+    pub(crate) source: Option<BufSource>,
+}
+
+#[derive(Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+#[schemars(rename = "RepeatOpts")]
+pub(crate) struct RepeatFields {
+    path: Option<String>,
+    #[schemars(with = "Option<i32>")]
+    buf: Option<Buffer>,
+}
+
+impl RepeatOpts {
+    pub fn parse_obj(object: Object) -> anyhow::Result<Self> {
+        let fields: RepeatFields = validate::deserialize(object, "repeat_opts")?;
+        let source = if fields.path.is_none() && fields.buf.is_none() {
+            None
+        } else {
+            Some(buf_source(fields.path, fields.buf, "repeat_opts")?)
         };
-        let mut path = None;
-        let mut opts = None;
-        let mut focus_new = None;
-        for (tag, obj) in dict {
-            let str_tag = tag
-                .to_str()
-                .context("unexpected non-utf-8 field in 'open_over_opts'")?;
-            match str_tag {
-                "path" => {
-                    parse_from_obj_with_err!(path, obj, String);
-                }
-                "opts" => {
-                    opts = Some(Opts::parse_obj(obj)?);
-                }
-                "focus_new" => {
-                    parse_from_obj_with_err!(focus_new, obj, bool);
-                }
-                unk => bail!("failed to parse 'open_over_opts' member, '{unk}' not recognized"),
-            }
+        Ok(Self { source })
+    }
+}
+
+pub struct PlaceRelativeOpts {
+    pub(crate) source_win: Option<Window>,
+    pub(crate) target_win: Option<Window>,
+    pub(crate) position: Option<Positions>,
+    pub(crate) focus_new: bool,
+    pub(crate) relative_keys: RelativeKeys,
+    pub(crate) opts: Opts,
+}
+
+#[derive(Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+#[schemars(rename = "PlaceRelativeOpts")]
+pub(crate) struct PlaceRelativeFields {
+    #[schemars(with = "Option<i32>")]
+    source_win: Option<Window>,
+    #[schemars(with = "Option<i32>")]
+    target_win: Option<Window>,
+    position: Option<Positions>,
+    opts: Option<NestedOpts>,
+    focus_new: Option<bool>,
+    relative_chars: Option<String>,
+    relative_keys: Option<RelativeKeys>,
+    relative_positions: Option<Vec<Positions>>,
+}
+
+impl PlaceRelativeOpts {
+    pub fn parse_obj(object: Object) -> anyhow::Result<Self> {
+        let fields: PlaceRelativeFields = validate::deserialize(object, "place_relative_opts")?;
+        let opts = nested_opts(fields.opts)?;
+        // Nothing to pick the position with if it's given
+        let relative_keys = if fields.position.is_some() {
+            RelativeKeys::default()
+        } else {
+            RelativeKeys::from_provided(
+                fields.relative_chars,
+                fields.relative_keys,
+                fields.relative_positions,
+                &opts,
+            )?
+        };
+        Ok(Self {
+            source_win: fields.source_win,
+            target_win: fields.target_win,
+            position: fields.position,
+            focus_new: fields.focus_new.unwrap_or_else(default_true),
+            relative_keys,
+            opts,
+        })
+    }
+}
+
+pub struct OpenManyOpts {
+    pub(crate) paths: Vec<String>,
+    pub(crate) sizing: SplitSizing,
+    pub(crate) focus_new: bool,
+    pub(crate) relative_keys: RelativeKeys,
+    pub(crate) opts: Opts,
+}
+
+#[derive(Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+#[schemars(rename = "OpenManyOpts")]
+pub(crate) struct OpenManyFields {
+    paths: Option<Vec<String>>,
+    opts: Option<NestedOpts>,
+    relative_chars: Option<String>,
+    relative_keys: Option<RelativeKeys>,
+    relative_positions: Option<Vec<Positions>>,
+    #[serde(default, deserialize_with = "deserialize_split_size")]
+    size: Option<SplitSize>,
+    pick_size: Option<bool>,
+    focus_new: Option<bool>,
+}
+
+impl OpenManyOpts {
+    pub fn parse_obj(object: Object) -> anyhow::Result<Self> {
+        let fields: OpenManyFields = validate::deserialize(object, "open_many_opts")?;
+        let paths = fields
+            .paths
+            .context("'open_many_opts' needs 'paths' to be set")?;
+        if paths.is_empty() {
+            bail!("'open_many_opts' needs at least one path in 'paths'");
         }
 
+        let opts = nested_opts(fields.opts)?;
+        let relative_keys = RelativeKeys::from_provided(
+            fields.relative_chars,
+            fields.relative_keys,
+            fields.relative_positions,
+            &opts,
+        )?;
         Ok(Self {
-            path: path.context("'open_split_opts' needs 'path' to be set")?,
-            focus_new: focus_new.unwrap_or_else(default_true),
-            opts: opts.unwrap_or_default(),
+            paths,
+            sizing: SplitSizing {
+                size: fields.size,
+                prompt: fields.pick_size.unwrap_or_default(),
+            },
+            focus_new: fields.focus_new.unwrap_or_else(default_true),
+            relative_keys,
+            opts,
         })
     }
 }
 
-pub struct OpenRelativeOpts {
-    pub(crate) path: String,
+pub struct DockFloatOpts {
     pub(crate) focus_new: bool,
-    pub(crate) relative_chars: String,
+    pub(crate) relative_keys: RelativeKeys,
     pub(crate) opts: Opts,
 }
 
-impl OpenRelativeOpts {
+#[derive(Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+#[schemars(rename = "DockFloatOpts")]
+pub(crate) struct DockFloatFields {
+    opts: Option<NestedOpts>,
+    focus_new: Option<bool>,
+    relative_chars: Option<String>,
+    relative_keys: Option<RelativeKeys>,
+    relative_positions: Option<Vec<Positions>>,
+}
+
+impl DockFloatOpts {
     pub fn parse_obj(object: Object) -> anyhow::Result<Self> {
-        let dict = obj_to_dict(object).context("invalid table")?;
-        let Some(dict) = dict else {
-            bail!("'open_over_opts' needs at least a path");
+        let fields: DockFloatFields = validate::deserialize(object, "dock_float_opts")?;
+        let opts = nested_opts(fields.opts)?;
+        let relative_keys = RelativeKeys::from_provided(
+            fields.relative_chars,
+            fields.relative_keys,
+            fields.relative_positions,
+            &opts,
+        )?;
+        Ok(Self {
+            focus_new: fields.focus_new.unwrap_or_else(default_true),
+            relative_keys,
+            opts,
+        })
+    }
+}
+
+pub struct MoveToTabOpts {
+    pub(crate) focus_new: bool,
+    pub(crate) relative: bool,
+    pub(crate) relative_keys: RelativeKeys,
+    pub(crate) opts: Opts,
+}
+
+#[derive(Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+#[schemars(rename = "MoveToTabOpts")]
+pub(crate) struct MoveToTabFields {
+    opts: Option<NestedOpts>,
+    focus_new: Option<bool>,
+    relative: Option<bool>,
+    relative_chars: Option<String>,
+    relative_keys: Option<RelativeKeys>,
+    relative_positions: Option<Vec<Positions>>,
+}
+
+impl MoveToTabOpts {
+    pub fn parse_obj(object: Object) -> anyhow::Result<Self> {
+        let fields: MoveToTabFields = validate::deserialize(object, "move_to_tab_opts")?;
+        let opts = nested_opts(fields.opts)?;
+        let relative = fields.relative.unwrap_or_default();
+        // Only used for picking where to move to within the tab
+        let relative_keys = if relative {
+            RelativeKeys::from_provided(
+                fields.relative_chars,
+                fields.relative_keys,
+                fields.relative_positions,
+                &opts,
+            )?
+        } else {
+            RelativeKeys::default()
         };
-        let mut path = None;
-        let mut opts = None;
-        let mut relative_chars = None;
-        let mut focus_new = None;
-        for (tag, obj) in dict {
-            let str_tag = tag
-                .to_str()
-                .context("unexpected non-utf-8 field in 'open_over_opts'")?;
-            match str_tag {
-                "path" => {
-                    parse_from_obj_with_err!(path, obj, String);
-                }
-                "opts" => {
-                    opts = Some(Opts::parse_obj(obj)?);
+        Ok(Self {
+            focus_new: fields.focus_new.unwrap_or_else(default_true),
+            relative,
+            relative_keys,
+            opts,
+        })
+    }
+}
+
+fn buf_source(path: Option<String>, buf: Option<Buffer>, name: &str) -> anyhow::Result<BufSource> {
+    match (path, buf) {
+        (Some(path), None) => Ok(BufSource::Path(path)),
+        (None, Some(buf)) => Ok(BufSource::Buf(buf)),
+        (Some(_), Some(_)) => bail!("'{name}' can only have one of 'path' and 'buf' set"),
+        (None, None) => bail!("'{name}' needs 'path' or 'buf' to be set"),
+    }
+}
+
+/// Integers are sizes, floats ratios, which serde's integer and float types would blur
+fn deserialize_split_size<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<SplitSize>, D::Error> {
+    let obj = Object::deserialize(deserializer)?;
+    split_size(obj)
+        .map(Some)
+        .map_err(|e| D::Error::custom(format!("{e:#}")))
+}
+
+/// A positive integer is an absolute size, a float between 0 and 1 a ratio
+fn split_size(obj: Object) -> anyhow::Result<SplitSize> {
+    match obj.kind() {
+        ObjectKind::Integer => {
+            let size = i64::from_object(obj).context("invalid 'size'")?;
+            match u32::try_from(size) {
+                Ok(size) if size > 0 => Ok(SplitSize::Absolute(size)),
+                _ => bail!("'size' needs to be a positive number of rows or columns, got {size}"),
+            }
+        }
+        ObjectKind::Float => {
+            let ratio = f64::from_object(obj).context("invalid 'size'")?;
+            if ratio > 0.0 && ratio < 1.0 {
+                Ok(SplitSize::Ratio(ratio))
+            } else {
+                bail!("'size' as a ratio needs to be between 0 and 1, got {ratio}")
+            }
+        }
+        t => bail!("'size' needs to be an integer or a ratio, got {t:?}"),
+    }
+}
+
+fn location(
+    lnum: Option<usize>,
+    col: Option<usize>,
+    end_lnum: Option<usize>,
+    name: &str,
+) -> anyhow::Result<Option<Location>> {
+    let Some(lnum) = lnum else {
+        if col.is_some() || end_lnum.is_some() {
+            bail!("'{name}' needs 'lnum' to be set if 'col' or 'end_lnum' is set");
+        }
+        return Ok(None);
+    };
+    if lnum == 0 {
+        bail!("'{name}' 'lnum' is 1-indexed, got 0");
+    }
+    let col = col.unwrap_or(1);
+    if col == 0 {
+        bail!("'{name}' 'col' is 1-indexed, got 0");
+    }
+    if let Some(end) = end_lnum {
+        if end < lnum {
+            bail!("'{name}' 'end_lnum' ({end}) is before 'lnum' ({lnum})");
+        }
+    }
+    Ok(Some(Location {
+        lnum,
+        col,
+        end_lnum,
+    }))
+}
+
+/// Which char picks which position in the relative pick
+#[derive(Debug, Clone, Default)]
+pub(crate) struct RelativeKeys(Vec<(Positions, char)>);
+
+impl RelativeKeys {
+    /// Maps chars onto `positions` in order, every position needs a char
+    fn from_chars(chars: &str, positions: &[Positions], name: &str) -> anyhow::Result<Self> {
+        let count = chars.chars().count();
+        if count < positions.len() {
+            bail!(
+                "'{name}' has {count} chars, but {} positions need one each, supply more or choose fewer with 'relative_positions'",
+                positions.len()
+            );
+        }
+        Ok(Self(positions.iter().copied().zip(chars.chars()).collect()))
+    }
+
+    /// Explicit keys take precedence, then provided chars, falling back to `selection_chars`.
+    /// Chars are assigned to `relative_positions` in order if given, otherwise to the
+    /// positions of [`Positions::defaults`]
+    fn from_provided(
+        relative_chars: Option<String>,
+        relative_keys: Option<Self>,
+        relative_positions: Option<Vec<Positions>>,
+        opts: &Opts,
+    ) -> anyhow::Result<Self> {
+        let chosen = relative_positions.is_some();
+        let positions = if let Some(positions) = relative_positions {
+            if relative_keys.is_some() {
+                bail!("'relative_positions' can't be combined with 'relative_keys', the keys already choose the positions");
+            }
+            check_relative_positions(positions)?
+        } else {
+            Positions::defaults().to_vec()
+        };
+        match (relative_chars, relative_keys) {
+            (Some(_), Some(_)) => {
+                bail!("only one of 'relative_chars' and 'relative_keys' can be set")
+            }
+            (None, Some(keys)) => {
+                keys.check_selection_overlap(opts, "relative_keys")?;
+                Ok(keys)
+            }
+            (Some(chars), None) => {
+                validate_provided_relative_chars(&chars)?;
+                let keys = Self::from_chars(&chars, &positions, "relative_chars")?;
+                if chosen {
+                    keys.check_selection_overlap(opts, "relative_chars")?;
                 }
-                "focus_new" => {
-                    parse_from_obj_with_err!(focus_new, obj, bool);
+                Ok(keys)
+            }
+            (None, None) => Self::from_chars(&opts.selection_chars, &positions, "selection_chars"),
+        }
+    }
+
+    /// Keys chosen per position are picked one by one, make sure none of them also picks a
+    /// window. Chars for the fixed order are left alone, they've always been allowed to overlap
+    fn check_selection_overlap(&self, opts: &Opts, name: &str) -> anyhow::Result<()> {
+        if let Some((pos, ch)) = self.iter().find(|(_, ch)| {
+            opts.selection_chars
+                .chars()
+                .any(|sel| sel.eq_ignore_ascii_case(ch))
+        }) {
+            bail!("invalid, '{name}' and 'selection_chars' overlap with '{ch}' for {pos:?}");
+        }
+        Ok(())
+    }
+
+    pub(crate) fn iter(&self) -> impl Iterator<Item = (Positions, char)> + '_ {
+        self.0.iter().copied()
+    }
+
+    pub(crate) fn position_for_char(&self, user: char) -> Option<Positions> {
+        self.iter()
+            .find(|(_, ch)| ch.eq_ignore_ascii_case(&user))
+            .map(|(pos, _)| pos)
+    }
+}
+
+/// `{ <position> = <char> }`, chars are unique regardless of case
+impl<'de> Deserialize<'de> for RelativeKeys {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct KeysVisitor;
+
+        impl<'de> Visitor<'de> for KeysVisitor {
+            type Value = RelativeKeys;
+
+            fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str("a table of positions to chars")
+            }
+
+            fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+                let mut keys: Vec<(Positions, char)> = Vec::new();
+                while let Some((pos, ch)) = map.next_entry::<Positions, char>()? {
+                    if keys
+                        .iter()
+                        .any(|(_, existing)| existing.eq_ignore_ascii_case(&ch))
+                    {
+                        return Err(A::Error::custom(format!(
+                            "duplicate char '{ch}' in 'relative_keys'"
+                        )));
+                    }
+                    keys.push((pos, ch));
                 }
-                "relative_chars" => {
-                    parse_from_obj_with_err!(relative_chars, obj, String);
+                if keys.is_empty() {
+                    return Err(A::Error::custom(
+                        "'relative_keys' needs at least one position",
+                    ));
                 }
-                unk => bail!("failed to parse 'open_over_opts' member, '{unk}' not recognized"),
+                Ok(RelativeKeys(keys))
             }
         }
 
-        let opts = opts.unwrap_or_default();
-        let relative_chars = if let Some(rel) = relative_chars {
-            validate_provided_relative_chars(&rel)?;
-            rel
-        } else {
-            opts.selection_chars.clone()
-        };
-        // Don't need to check multiselect here, since it's not applicable for open relative
-        Ok(Self {
-            path: path.context("'open_split_opts' needs 'path' to be set")?,
-            focus_new: focus_new.unwrap_or_else(default_true),
-            relative_chars,
-            opts,
+        deserializer.deserialize_map(KeysVisitor)
+    }
+}
+
+impl JsonSchema for RelativeKeys {
+    fn schema_name() -> Cow<'static, str> {
+        "RelativeKeys".into()
+    }
+
+    fn json_schema(generator: &mut SchemaGenerator) -> Schema {
+        json_schema!({
+            "type": "object",
+            "propertyNames": generator.subschema_for::<Positions>(),
+            "additionalProperties": generator.subschema_for::<char>(),
         })
     }
 }
 
+fn check_relative_positions(positions: Vec<Positions>) -> anyhow::Result<Vec<Positions>> {
+    if positions.is_empty() {
+        bail!("'relative_positions' needs at least one position");
+    }
+    for (i, pos) in positions.iter().enumerate() {
+        if positions[..i].contains(pos) {
+            bail!("duplicate position {pos:?} in 'relative_positions'");
+        }
+    }
+    Ok(positions)
+}
+
 // Can theoretically be any char, just need to check for uniqueness
 fn validate_provided_relative_chars(chars: &str) -> anyhow::Result<()> {
     let mut set = std::collections::HashSet::with_capacity(chars.len());
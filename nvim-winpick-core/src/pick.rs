@@ -1,33 +1,55 @@
 use crate::{
     ctx::Context,
     draw::{FloatingBigLetterDrawer, FloatingLetterDrawer, PickBetweenWindowsDrawer},
+    hooks,
     notify::notify_warn,
     opts::Opts,
-    win::get_windows,
+    win::{get_windows, SplitSize, SplitSizing},
 };
 use anyhow::{Context as _, Result};
-use nvim_oxi::api::{opts::SetHighlightOptsBuilder, Window};
+use nvim_oxi::api::{
+    opts::{EchoOpts, SetHighlightOptsBuilder},
+    Window,
+};
 
+pub(crate) mod close;
+pub(crate) mod float;
+pub(crate) mod open_many;
 pub(crate) mod simple_open;
 pub(crate) mod simple_operations;
+pub(crate) mod stash;
+pub(crate) mod tab;
 pub(crate) mod win_relative;
 
 pub(crate) fn pick_window(opts: &Opts, ctx: &mut Context) -> Result<Option<Window>> {
-    let windows = get_windows(|_| true)?;
-    if windows.is_empty() {
-        return Ok(None);
-    }
-    let mut filtered_windows = Vec::with_capacity(windows.len());
-    let current_win = ctx.get_current_win();
-    for win in windows {
-        if opts.filter_rules.filter(&win, &current_win)? {
-            filtered_windows.push(win);
-        }
-    }
+    pick_window_matching(opts, ctx, |_| true)
+}
+
+/// Same as `pick_window`, but only windows matching `filter_fn` are candidates, before
+/// `filter_rules` are applied
+pub(crate) fn pick_window_matching<F: FnMut(&Window) -> bool>(
+    opts: &Opts,
+    ctx: &mut Context,
+    filter_fn: F,
+) -> Result<Option<Window>> {
+    hooks::pick_started(&opts.hooks);
+    let win = pick_one(opts, ctx, filter_fn)?;
+    hooks::picked(&opts.hooks, win.as_slice());
+    Ok(win)
+}
+
+fn pick_one<F: FnMut(&Window) -> bool>(
+    opts: &Opts,
+    ctx: &mut Context,
+    filter_fn: F,
+) -> Result<Option<Window>> {
+    let mut filtered_windows = filtered_windows(opts, ctx, filter_fn)?;
     if filtered_windows.is_empty() {
-        notify_warn("No windows left after filtering");
         return Ok(None);
     }
+    if let Some(ordinal) = ctx.take_ordinal() {
+        return Ok(nth_labelled(opts, filtered_windows, ordinal));
+    }
     if filtered_windows.len() == 1 && opts.filter_rules.autoselect_one {
         return Ok(filtered_windows.pop());
     }
@@ -44,6 +66,79 @@ pub(crate) fn pick_window(opts: &Opts, ctx: &mut Context) -> Result<Option<Windo
     Ok(win)
 }
 
+/// The windows in the current tab matching `filter_fn` and `filter_rules`, warns if windows
+/// were present but all of them were filtered out
+pub(crate) fn filtered_windows<F: FnMut(&Window) -> bool>(
+    opts: &Opts,
+    ctx: &mut Context,
+    filter_fn: F,
+) -> Result<Vec<Window>> {
+    let windows = get_windows(filter_fn)?;
+    if windows.is_empty() {
+        return Ok(windows);
+    }
+    let mut filtered_windows = Vec::with_capacity(windows.len());
+    let current_win = ctx.get_current_win();
+    for win in windows {
+        if opts.filter_rules.filter(&win, &current_win)? {
+            filtered_windows.push(win);
+        }
+    }
+    if filtered_windows.is_empty() {
+        notify_warn("No windows left after filtering");
+    }
+    Ok(filtered_windows)
+}
+
+/// The window that would get the `ordinal`-th label (1-based), labels are handed out in order
+/// until the `selection_chars` run out
+fn nth_labelled(opts: &Opts, windows: Vec<Window>, ordinal: usize) -> Option<Window> {
+    let labelled = windows.len().min(opts.selection_chars.chars().count());
+    if ordinal == 0 || ordinal > labelled {
+        notify_warn(&format!(
+            "[nvim_winpick] no window labelled {ordinal}, only {labelled} candidates"
+        ));
+        return None;
+    }
+    windows.into_iter().nth(ordinal - 1)
+}
+
+/// `getchar()` returns keys as their char code
+pub(crate) const ESC: u32 = 0x1b;
+
+/// What the size prompt ended with
+pub(crate) enum PickedSize {
+    Size(Option<SplitSize>),
+    Cancelled,
+}
+
+/// The size for a new split, if `sizing` asks for it, prompts for a digit where `1` is 10% of
+/// the space up to `9` at 90%, `<Esc>` cancels and any other key keeps the configured size
+pub(crate) fn pick_split_size(sizing: SplitSizing) -> Result<PickedSize> {
+    if !sizing.prompt {
+        return Ok(PickedSize::Size(sizing.size));
+    }
+    nvim_oxi::api::echo(
+        [("[nvim_winpick] size: 1-9 for 10%-90%", None::<&str>)],
+        false,
+        &EchoOpts::default(),
+    )
+    .context("failed to show size prompt")?;
+    nvim_oxi::api::command("redraw").context("failed to redraw")?;
+    let ch: u32 = nvim_oxi::api::call_function("getchar", ((),)).context("failed to get char")?;
+    nvim_oxi::api::echo([("", None::<&str>)], false, &EchoOpts::default())
+        .context("failed to clear size prompt")?;
+    if ch == ESC {
+        return Ok(PickedSize::Cancelled);
+    }
+    let digit = char::from_u32(ch)
+        .and_then(|ch| ch.to_digit(10))
+        .filter(|digit| *digit > 0);
+    Ok(PickedSize::Size(digit.map_or(sizing.size, |digit| {
+        Some(SplitSize::Ratio(f64::from(digit) / 10.0))
+    })))
+}
+
 fn exec_draw<D>(mut drawer: D) -> anyhow::Result<Option<Window>>
 where
     D: PickBetweenWindowsDrawer,
@@ -58,21 +153,22 @@ where
 }
 
 pub(crate) fn try_pick_multi_window(opts: &Opts, ctx: &mut Context) -> Result<Vec<Window>> {
-    let windows = get_windows(|_| true)?;
-    if windows.is_empty() {
-        return Ok(vec![]);
-    }
-    let mut filtered_windows = Vec::with_capacity(windows.len());
-    let current_win = ctx.get_current_win();
-    for win in windows {
-        if opts.filter_rules.filter(&win, &current_win)? {
-            filtered_windows.push(win);
-        }
-    }
+    hooks::pick_started(&opts.hooks);
+    let wins = pick_many(opts, ctx)?;
+    hooks::picked(&opts.hooks, &wins);
+    Ok(wins)
+}
+
+fn pick_many(opts: &Opts, ctx: &mut Context) -> Result<Vec<Window>> {
+    let filtered_windows = filtered_windows(opts, ctx, |_| true)?;
     if filtered_windows.is_empty() {
-        notify_warn("No windows left after filtering");
         return Ok(vec![]);
     }
+    if let Some(ordinal) = ctx.take_ordinal() {
+        return Ok(nth_labelled(opts, filtered_windows, ordinal)
+            .into_iter()
+            .collect());
+    }
     if filtered_windows.len() == 1 && opts.filter_rules.autoselect_one {
         return Ok(filtered_windows);
     }
@@ -0,0 +1,269 @@
+use anyhow::Context as _;
+use nvim_oxi::api::{Buffer, Window};
+use nvim_oxi::{Array, Dictionary, Object};
+use schemars::JsonSchema;
+use serde::Deserialize;
+
+use crate::ctx::Context;
+use crate::notify::notify_warn;
+use crate::opts::{Bo, CloseOpts};
+use crate::win::{get_windows, is_floating};
+
+/// What to do when closing the last window of a buffer with unsaved changes, `Prompt` asks
+/// whether to save, discard, or skip the window
+#[derive(Default, Clone, Copy, Debug, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum ModifiedAction {
+    #[default]
+    Prompt,
+    Skip,
+    Discard,
+}
+
+/// What to do with the buffer of a closed window, if it isn't shown in another window
+#[derive(Default, Clone, Copy, Debug, Eq, PartialEq, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum DeleteBuffer {
+    #[default]
+    Keep,
+    Bdelete,
+    Bwipeout,
+}
+
+#[derive(Default)]
+pub(crate) struct CloseSummary {
+    closed: Vec<Window>,
+    skipped: Vec<(Window, String)>,
+}
+
+impl CloseSummary {
+    /// `{ closed = { <winid> }, skipped = { { win = <winid>, reason = <reason> } } }`
+    pub(crate) fn into_dict(self) -> Dictionary {
+        let closed: Array = self
+            .closed
+            .into_iter()
+            .map(|win| Object::from(win.handle()))
+            .collect();
+        let skipped: Array = self
+            .skipped
+            .into_iter()
+            .map(|(win, reason)| {
+                Object::from(Dictionary::from_iter([
+                    ("win", Object::from(win.handle())),
+                    ("reason", Object::from(reason)),
+                ]))
+            })
+            .collect();
+        Dictionary::from_iter([("closed", closed), ("skipped", skipped)])
+    }
+}
+
+pub(crate) fn pick_close_window(
+    close_opts: &CloseOpts,
+    ctx: &mut Context,
+) -> anyhow::Result<CloseSummary> {
+    let windows = super::try_pick_multi_window(&close_opts.opts, ctx)?;
+    Ok(close_all(windows, close_opts))
+}
+
+/// Closes every window in the tab matching the filter rules, except for the picked ones
+pub(crate) fn pick_only_windows(
+    close_opts: &CloseOpts,
+    ctx: &mut Context,
+) -> anyhow::Result<CloseSummary> {
+    let keep = super::try_pick_multi_window(&close_opts.opts, ctx)?;
+    let Some(first_kept) = keep.first().cloned() else {
+        return Ok(CloseSummary::default());
+    };
+    let current_win = ctx.get_current_win();
+    let mut others = Vec::new();
+    for win in get_windows(|win| !keep.contains(win))? {
+        if close_opts.opts.filter_rules.filter(&win, &current_win)? {
+            others.push(win);
+        }
+    }
+    let summary = close_all(others, close_opts);
+    if first_kept.is_valid() {
+        nvim_oxi::api::set_current_win(&first_kept).context("failed to focus kept window")?;
+    }
+    Ok(summary)
+}
+
+fn close_all(windows: Vec<Window>, close_opts: &CloseOpts) -> CloseSummary {
+    let mut summary = CloseSummary::default();
+    for window in windows {
+        match close_checked(&window, close_opts) {
+            Ok(None) => summary.closed.push(window),
+            Ok(Some(reason)) => summary.skipped.push((window, reason)),
+            Err(e) => summary.skipped.push((window, format!("{e:#}"))),
+        }
+    }
+    if !summary.skipped.is_empty() {
+        let reasons: Vec<String> = summary
+            .skipped
+            .iter()
+            .map(|(win, reason)| format!("window {}: {reason}", win.handle()))
+            .collect();
+        notify_warn(&format!(
+            "[nvim_winpick] skipped closing {} window(s)\n{}",
+            reasons.len(),
+            reasons.join("\n")
+        ));
+    }
+    summary
+}
+
+/// Closes `window` if it's safe to do so, returns why it was skipped otherwise
+fn close_checked(window: &Window, close_opts: &CloseOpts) -> anyhow::Result<Option<String>> {
+    if !window.is_valid() {
+        return Ok(Some("window is no longer valid".to_string()));
+    }
+    if let Some(reason) = last_window(window)? {
+        return Ok(Some(reason.to_string()));
+    }
+    let buf = window.get_buf().context("failed to get window buffer")?;
+    if is_protected(&buf, &close_opts.protect)? {
+        return Ok(Some("protected buffer".to_string()));
+    }
+    let shown_in: Vec<i32> = nvim_oxi::api::call_function("win_findbuf", (buf.handle(),))
+        .context("failed to find windows showing buffer")?;
+    let last_of_buffer = shown_in.len() <= 1;
+    #[allow(deprecated)]
+    let modified: bool = buf
+        .get_option("modified")
+        .context("failed to get buf modified")?;
+    let mut discard = false;
+    // Changes aren't at stake if the buffer stays open in another window
+    if modified && last_of_buffer {
+        let resolution = match close_opts.modified {
+            ModifiedAction::Prompt => prompt_modified(&buf)?,
+            ModifiedAction::Skip => Resolution::Skip,
+            ModifiedAction::Discard => Resolution::Discard,
+        };
+        match resolution {
+            Resolution::Skip => return Ok(Some("unsaved changes".to_string())),
+            Resolution::Discard => discard = true,
+            Resolution::Save => {
+                if let Err(e) = win_execute(window, "write") {
+                    return Ok(Some(format!("failed to save: {e:#}")));
+                }
+            }
+        }
+    }
+    // Without deleting the buffer, reset it so the changes are gone rather than hidden
+    if discard && close_opts.delete_buffer == DeleteBuffer::Keep {
+        if let Err(e) = reset_buffer(window, &buf) {
+            return Ok(Some(format!("failed to discard changes: {e:#}")));
+        }
+    }
+    window
+        .clone()
+        .close(discard)
+        .context("failed to close window")?;
+    if last_of_buffer {
+        let bang = if discard { "!" } else { "" };
+        let cmd = match close_opts.delete_buffer {
+            DeleteBuffer::Keep => return Ok(None),
+            DeleteBuffer::Bdelete => "bdelete",
+            DeleteBuffer::Bwipeout => "bwipeout",
+        };
+        nvim_oxi::api::command(&format!("{cmd}{bang} {}", buf.handle()))
+            .context("closed window but failed to delete its buffer")?;
+    }
+    Ok(None)
+}
+
+/// What was decided for a modified buffer
+enum Resolution {
+    Save,
+    Discard,
+    Skip,
+}
+
+fn prompt_modified(buf: &Buffer) -> anyhow::Result<Resolution> {
+    let name = buf.get_name().context("failed to get buf name")?;
+    let name = if name.as_os_str().is_empty() {
+        "[No Name]".to_string()
+    } else {
+        name.display().to_string()
+    };
+    let choice: i64 = nvim_oxi::api::call_function(
+        "confirm",
+        (
+            format!("Save changes to \"{name}\"?"),
+            "&Save\n&Discard\n&Cancel",
+            1,
+        ),
+    )
+    .context("failed to prompt for unsaved changes")?;
+    Ok(match choice {
+        1 => Resolution::Save,
+        2 => Resolution::Discard,
+        // Cancel or <Esc>
+        _ => Resolution::Skip,
+    })
+}
+
+fn win_execute(window: &Window, cmd: &str) -> anyhow::Result<()> {
+    nvim_oxi::api::call_function::<_, String>("win_execute", (window.handle(), cmd))
+        .with_context(|| format!("failed to run '{cmd}'"))?;
+    Ok(())
+}
+
+/// Reloads a named buffer from disk, `:edit!` fails on unnamed buffers (E32), those are emptied
+fn reset_buffer(window: &Window, buf: &Buffer) -> anyhow::Result<()> {
+    let name = buf.get_name().context("failed to get buf name")?;
+    if !name.as_os_str().is_empty() {
+        return win_execute(window, "edit!");
+    }
+    let mut buf = buf.clone();
+    buf.set_lines(.., false, [""])
+        .context("failed to clear buffer")?;
+    #[allow(deprecated)]
+    buf.set_option("modified", false)
+        .context("failed to set buf nomodified")?;
+    Ok(())
+}
+
+/// Why `window` can't be closed as the last split of its tab, floats can always be closed.
+/// Only the last split of all tabs would fail to close, but the pick is about the tab, closing
+/// it along with its last window would be a surprise. Closing the last split next to floats
+/// fails with E5601
+pub(crate) fn last_window(window: &Window) -> anyhow::Result<Option<&'static str>> {
+    if is_floating(window) {
+        return Ok(None);
+    }
+    let tab = window.get_tabpage().context("failed to get window tab")?;
+    let (floats, splits): (Vec<Window>, Vec<Window>) = tab
+        .list_wins()
+        .context("failed to list tab windows")?
+        .partition(is_floating);
+    Ok(match splits.len() {
+        0 | 1 if floats.is_empty() => Some("last window"),
+        0 | 1 => Some("only floating windows would remain"),
+        _ => None,
+    })
+}
+
+fn is_protected(buf: &Buffer, protect: &Bo) -> anyhow::Result<bool> {
+    // Alternative is umapped in nvim_oxi so far
+    #[allow(deprecated)]
+    if !protect.filetype.is_empty() {
+        let ft: String = buf
+            .get_option("filetype")
+            .context("failed to get buf filetype")?;
+        if protect.filetype.contains(&ft) {
+            return Ok(true);
+        }
+    }
+    #[allow(deprecated)]
+    if !protect.buftype.is_empty() {
+        let bt: String = buf
+            .get_option("buftype")
+            .context("failed to get buf buftype")?;
+        if protect.buftype.contains(&bt) {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
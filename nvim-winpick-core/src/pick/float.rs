@@ -0,0 +1,51 @@
+use anyhow::Context as _;
+use nvim_oxi::api::Window;
+
+use crate::ctx::Context;
+use crate::hooks;
+use crate::opts::{Opts, RelativeKeys};
+use crate::win::{float_window, is_floating, place_window_relative};
+
+pub(crate) fn pick_float_window(opts: &Opts, ctx: &mut Context) -> anyhow::Result<()> {
+    let Some(mut window) = super::pick_window_matching(opts, ctx, |w| !is_floating(w))? else {
+        return Ok(());
+    };
+    float_window(&mut window)?;
+    nvim_oxi::api::set_current_win(&window).context("failed to focus floated window")?;
+    Ok(())
+}
+
+pub(crate) fn pick_dock_float(
+    focus_new: bool,
+    relative_keys: &RelativeKeys,
+    opts: &Opts,
+    ctx: &mut Context,
+) -> anyhow::Result<()> {
+    let refocus = (!focus_new).then(|| ctx.get_current_win());
+    // Floats need to be pickable here regardless of the configured filter rules
+    let mut float_opts = opts.clone();
+    float_opts.filter_rules.include_floating = true;
+    let Some(mut float) = super::pick_window_matching(&float_opts, ctx, is_floating)? else {
+        return Ok(());
+    };
+    let Some(mut target) = super::pick_window_matching(opts, ctx, |w| !is_floating(w))? else {
+        return Ok(());
+    };
+    let Some(pos) = super::win_relative::pick_relative_position(
+        &target,
+        relative_keys,
+        opts.relative_preview,
+        None,
+    )?
+    else {
+        hooks::cancelled(&opts.hooks);
+        return Ok(());
+    };
+    place_window_relative(&mut float, &mut target, pos)?;
+    if let Some(refocus) = refocus.filter(Window::is_valid) {
+        nvim_oxi::api::set_current_win(&refocus).context("failed to refocus old window")?;
+    } else {
+        nvim_oxi::api::set_current_win(&float).context("failed to focus docked window")?;
+    }
+    Ok(())
+}
@@ -0,0 +1,147 @@
+use anyhow::Context as _;
+use nvim_oxi::api::{opts::EchoOpts, Window};
+
+use crate::buf::BufSource;
+use crate::ctx::Context;
+use crate::draw::{FloatingBigLetterDrawer, FloatingLetterDrawer, PickBetweenWindowsDrawer};
+use crate::geometry::Positions;
+use crate::hint::Hint;
+use crate::hooks;
+use crate::opts::{Opts, RelativeKeys};
+use crate::pick::PickedSize;
+use crate::win::{open_split_with, SplitSize, SplitSizing};
+
+/// Picks a window and a relative position for each path, then opens all of them.
+/// Filtering and drawing the window hints only happens once, if any pick is cancelled
+/// nothing is opened.
+pub(crate) fn pick_open_many(
+    paths: &[String],
+    sizing: SplitSizing,
+    focus_new: bool,
+    relative_keys: &RelativeKeys,
+    opts: &Opts,
+    ctx: &mut Context,
+) -> anyhow::Result<()> {
+    let refocus = (!focus_new).then(|| ctx.get_current_win());
+    hooks::pick_started(&opts.hooks);
+    let windows = super::filtered_windows(opts, ctx, |_| true)?;
+    if windows.is_empty() {
+        hooks::picked(&opts.hooks, &[]);
+        return Ok(());
+    }
+    let autoselected =
+        (windows.len() == 1 && opts.filter_rules.autoselect_one).then(|| windows[0].clone());
+    let assignments = match opts.hint {
+        Hint::FloatingBigLetter => assign_targets(
+            FloatingBigLetterDrawer::new(&opts.selection_chars, &windows),
+            paths,
+            autoselected.as_ref(),
+            sizing,
+            relative_keys,
+            opts.relative_preview,
+        ),
+        Hint::FloatingLetter => assign_targets(
+            FloatingLetterDrawer::new_pick_between(&opts.selection_chars, &windows),
+            paths,
+            autoselected.as_ref(),
+            sizing,
+            relative_keys,
+            opts.relative_preview,
+        ),
+    }?;
+    let mut picked: Vec<Window> = Vec::new();
+    for (win, _, _) in assignments.iter().flatten() {
+        if !picked.contains(win) {
+            picked.push(win.clone());
+        }
+    }
+    hooks::picked(&opts.hooks, &picked);
+    let Some(assignments) = assignments else {
+        return Ok(());
+    };
+    let mut last_opened = None;
+    for (path, (mut win, pos, size)) in paths.iter().zip(assignments) {
+        // Splits are made from the current window
+        nvim_oxi::api::set_current_win(&win).context("failed to focus picked window")?;
+        let source = BufSource::Path(path.clone());
+        last_opened = Some(open_split_with(&source, None, &mut win, pos, size)?);
+    }
+    if let Some(refocus) = refocus {
+        nvim_oxi::api::set_current_win(&refocus).context("failed to refocus old window")?;
+    } else if let Some(last_opened) = last_opened {
+        nvim_oxi::api::set_current_win(&last_opened).context("failed to focus new window")?;
+    }
+    Ok(())
+}
+
+/// Where, and how large, to open one of the paths
+type Assignment = (Window, Positions, Option<SplitSize>);
+
+fn assign_targets<D>(
+    mut drawer: D,
+    paths: &[String],
+    autoselected: Option<&Window>,
+    sizing: SplitSizing,
+    relative_keys: &RelativeKeys,
+    preview: bool,
+) -> anyhow::Result<Option<Vec<Assignment>>>
+where
+    D: PickBetweenWindowsDrawer,
+{
+    if autoselected.is_none() {
+        drawer.draw()?;
+    }
+    let assignments = prompt_targets(&drawer, paths, autoselected, sizing, relative_keys, preview);
+    // However the prompts ended, the last one would stay on the command line otherwise
+    nvim_oxi::api::echo([("", None::<&str>)], false, &EchoOpts::default())
+        .context("failed to clear path prompt")?;
+    nvim_oxi::api::command("redraw").context("failed to redraw")?;
+    assignments
+}
+
+/// Prompts with each path for its window, position and size, `None` if any prompt is cancelled
+fn prompt_targets<D>(
+    drawer: &D,
+    paths: &[String],
+    autoselected: Option<&Window>,
+    sizing: SplitSizing,
+    relative_keys: &RelativeKeys,
+    preview: bool,
+) -> anyhow::Result<Option<Vec<Assignment>>>
+where
+    D: PickBetweenWindowsDrawer,
+{
+    let mut assignments = Vec::with_capacity(paths.len());
+    for (ind, path) in paths.iter().enumerate() {
+        let prompt = format!("[nvim_winpick] ({}/{}) {path}", ind + 1, paths.len());
+        nvim_oxi::api::echo([(prompt, None::<&str>)], false, &EchoOpts::default())
+            .context("failed to show path prompt")?;
+        let win = if let Some(win) = autoselected {
+            win.clone()
+        } else {
+            nvim_oxi::api::command("redraw").context("failed to redraw")?;
+            let ch: u32 =
+                nvim_oxi::api::call_function("getchar", ((),)).context("failed to get char")?;
+            let ch = char::from_u32(ch).with_context(|| format!("invalid char picked: {ch}"))?;
+            let Some(win) = drawer.target_win_for_char(ch) else {
+                return Ok(None);
+            };
+            win
+        };
+        let Some(pos) =
+            super::win_relative::pick_relative_position(&win, relative_keys, preview, sizing.size)?
+        else {
+            return Ok(None);
+        };
+        let size = if pos.split_axis().is_some() {
+            let PickedSize::Size(size) = crate::pick::pick_split_size(sizing)? else {
+                return Ok(None);
+            };
+            size
+        } else {
+            None
+        };
+        assignments.push((win, pos, size));
+    }
+    Ok(Some(assignments))
+}
@@ -1,46 +1,119 @@
+use crate::buf::BufSource;
 use crate::ctx::Context;
 use anyhow::{Context as _, Result};
 use nvim_oxi::api::types::WindowConfigBuilder;
+use nvim_oxi::api::Window;
 
-use crate::win::open_split_with;
+use crate::geometry::{Positions, SplitAxis};
+use crate::hooks;
+use crate::pick::PickedSize;
+use crate::repeat::{remember, Resolved};
+use crate::win::{
+    jump_to_location, open_split_with, resize_split, Location, SplitKind, SplitSize, SplitSizing,
+};
 
 use crate::opts::Opts;
 
 pub(crate) fn open_simple_split_at_win(
     focus_new: bool,
-    vertical: bool,
-    path: &str,
+    kind: SplitKind,
+    source: &BufSource,
+    location: Option<Location>,
+    sizing: SplitSizing,
     opts: &Opts,
     ctx: &mut Context,
 ) -> Result<()> {
-    let bufnr = crate::buf::load_file_to_hidden_buffer(path)?;
+    // Fail on a bad source before picking, the loaded buffer is what's opened
+    let loaded = BufSource::Buf(source.load()?);
     let Some(win) = crate::pick::pick_window(opts, ctx)? else {
         return Ok(());
     };
 
+    let PickedSize::Size(size) = crate::pick::pick_split_size(sizing)? else {
+        hooks::cancelled(&opts.hooks);
+        return Ok(());
+    };
+    split_at_win(&loaded, location, kind, size, win.clone(), focus_new)?;
+    remember(
+        Resolved::OpenSplit {
+            source: source.clone(),
+            location,
+            kind,
+            size,
+            window: win,
+            focus_new,
+        },
+        opts.dot_repeat,
+    )
+}
+
+/// Opens `source` in a split of `win`
+pub(crate) fn split_at_win(
+    source: &BufSource,
+    location: Option<Location>,
+    kind: SplitKind,
+    size: Option<SplitSize>,
+    win: Window,
+    focus_new: bool,
+) -> Result<()> {
+    let bufnr = source.load()?;
+    let axis = kind.axis();
+    let available = match axis {
+        SplitAxis::Cols => win.get_width().context("failed to get window width")?,
+        SplitAxis::Rows => win.get_height().context("failed to get window height")?,
+    };
     let mut opts_builder = WindowConfigBuilder::default();
-    let mut opts = opts_builder.vertical(vertical).build();
+    let mut opts = match kind {
+        SplitKind::Default { vertical } => opts_builder.vertical(vertical).build(),
+        SplitKind::Side(direction) => opts_builder.split(direction).build(),
+    };
     // Todo:  This should be exposed through the builder, it's not only through relative afaik,
     // check with nvim_oxi
     opts.win = Some(win);
-    nvim_oxi::api::open_win(&bufnr, focus_new, &opts).context("failed to open window")?;
+    let mut new_win =
+        nvim_oxi::api::open_win(&bufnr, focus_new, &opts).context("failed to open window")?;
+    if let Some(size) = size {
+        resize_split(&mut new_win, axis, available, size)?;
+    }
+    if let Some(location) = location {
+        jump_to_location(&mut new_win, location)?;
+    }
     Ok(())
 }
 
 pub(crate) fn open_over_win(
-    path: &str,
+    source: &BufSource,
+    location: Option<Location>,
     focus_new: bool,
     opts: &Opts,
     ctx: &mut Context,
 ) -> Result<()> {
-    let refocus = (!focus_new).then(|| ctx.get_current_win());
-    let Some(mut win) = crate::pick::pick_window(opts, ctx)? else {
+    let Some(win) = crate::pick::pick_window(opts, ctx)? else {
         return Ok(());
     };
-    open_split_with(
-        path,
-        refocus.as_ref(),
-        &mut win,
-        crate::geometry::Positions::Center,
+    open_over(source, location, win.clone(), focus_new)?;
+    remember(
+        Resolved::OpenOver {
+            source: source.clone(),
+            location,
+            window: win,
+            focus_new,
+        },
+        opts.dot_repeat,
     )
 }
+
+/// Shows `source` in `win`
+pub(crate) fn open_over(
+    source: &BufSource,
+    location: Option<Location>,
+    mut win: Window,
+    focus_new: bool,
+) -> Result<()> {
+    let refocus = (!focus_new).then(nvim_oxi::api::get_current_win);
+    let mut new_win = open_split_with(source, refocus.as_ref(), &mut win, Positions::Center, None)?;
+    if let Some(location) = location {
+        jump_to_location(&mut new_win, location)?;
+    }
+    Ok(())
+}
@@ -1,19 +1,15 @@
+use nvim_oxi::api::Window;
+
 use crate::ctx::Context;
 use crate::opts::Opts;
+use crate::repeat::{remember, Resolved};
 
 pub(crate) fn pick_focus_window(opts: &Opts, ctx: &mut Context) -> anyhow::Result<()> {
     let Some(window) = super::pick_window(opts, ctx)? else {
         return Ok(());
     };
     nvim_oxi::api::set_current_win(&window)?;
-    Ok(())
-}
-
-pub(crate) fn pick_close_window(opts: &Opts, ctx: &mut Context) -> anyhow::Result<()> {
-    for window in super::try_pick_multi_window(opts, ctx)? {
-        window.close(false)?;
-    }
-    Ok(())
+    remember(Resolved::Focus { window }, opts.dot_repeat)
 }
 
 pub(crate) fn pick_swap_window(
@@ -21,12 +17,24 @@ pub(crate) fn pick_swap_window(
     opts: &Opts,
     ctx: &mut Context,
 ) -> anyhow::Result<()> {
-    // Race condition here, buffer on window changes between checks
-    let mut cur_win = ctx.get_current_win();
-    let cur_buf = cur_win.get_buf()?;
-    let Some(mut target_win) = super::pick_window(opts, ctx)? else {
+    let Some(target_win) = super::pick_window(opts, ctx)? else {
         return Ok(());
     };
+    swap_with(target_win.clone(), focus_new)?;
+    remember(
+        Resolved::Swap {
+            window: target_win,
+            focus_new,
+        },
+        opts.dot_repeat,
+    )
+}
+
+/// Swaps the buffers of the current window and `target_win`
+pub(crate) fn swap_with(mut target_win: Window, focus_new: bool) -> anyhow::Result<()> {
+    // Race condition here, buffer on window changes between checks
+    let mut cur_win = nvim_oxi::api::get_current_win();
+    let cur_buf = cur_win.get_buf()?;
     let target_buf = target_win.get_buf()?;
     cur_win.set_buf(&target_buf)?;
     target_win.set_buf(&cur_buf)?;
@@ -0,0 +1,168 @@
+use std::cell::RefCell;
+
+use anyhow::{bail, Context as _};
+use nvim_oxi::api::{
+    types::{SplitDirection, WindowConfig},
+    Buffer, TabPage, Window,
+};
+use nvim_oxi::Array;
+
+use crate::ctx::Context;
+use crate::hooks::{self, Hooks};
+use crate::layout::{Layout, SplitPlacement};
+use crate::notify::notify_warn;
+use crate::opts::Opts;
+use crate::win::{is_floating, split_config};
+
+/// A window removed from the layout, with what's needed to put it back
+struct StashedWindow {
+    buf: Buffer,
+    tab: TabPage,
+    placement: Placement,
+    width: u32,
+    height: u32,
+    cursor: (usize, usize),
+}
+
+enum Placement {
+    Split(SplitPlacement),
+    Float(Box<WindowConfig>),
+}
+
+thread_local! {
+    /// Most recently stashed last, Neovim calls into the plugin from a single thread
+    static STASH: RefCell<Vec<StashedWindow>> = const { RefCell::new(Vec::new()) };
+}
+
+pub(crate) fn pick_stash_window(opts: &Opts, ctx: &mut Context) -> anyhow::Result<()> {
+    for window in super::try_pick_multi_window(opts, ctx)? {
+        let stashed = stash_window(&window)?;
+        STASH.with_borrow_mut(|stash| stash.push(stashed));
+    }
+    Ok(())
+}
+
+/// Records where `window` is and hides it, the layout is read fresh for every window since
+/// stashing one changes the placement of its neighbours
+fn stash_window(window: &Window) -> anyhow::Result<StashedWindow> {
+    let tab = window.get_tabpage().context("failed to get window tab")?;
+    let placement = if is_floating(window) {
+        let cfg = window.get_config().context("failed to get window config")?;
+        Placement::Float(Box::new(cfg))
+    } else {
+        let layout = Layout::of_tab(&tab)?;
+        let Some(placement) = layout.placement_of(window) else {
+            bail!("can't stash the only window in a tab");
+        };
+        Placement::Split(placement)
+    };
+    let stashed = StashedWindow {
+        buf: window.get_buf().context("failed to get window buffer")?,
+        tab,
+        placement,
+        width: window.get_width().context("failed to get window width")?,
+        height: window.get_height().context("failed to get window height")?,
+        cursor: window.get_cursor().context("failed to get window cursor")?,
+    };
+    // Hiding keeps the buffer loaded, even with unsaved changes
+    window.clone().hide().context("failed to hide window")?;
+    Ok(stashed)
+}
+
+/// Restores the most recently stashed window, or lets the user pick one from a list
+pub(crate) fn unstash(pick: bool, focus_new: bool, hooks: &Hooks) -> anyhow::Result<()> {
+    let stashed = if pick {
+        pick_stashed(hooks)?
+    } else {
+        STASH.with_borrow_mut(Vec::pop)
+    };
+    let Some(stashed) = stashed else {
+        if !pick {
+            notify_warn("[nvim_winpick] no stashed windows");
+        }
+        return Ok(());
+    };
+    let refocus = (!focus_new).then(nvim_oxi::api::get_current_win);
+    restore(&stashed)?;
+    if let Some(refocus) = refocus.filter(Window::is_valid) {
+        nvim_oxi::api::set_current_win(&refocus).context("failed to refocus old window")?;
+    }
+    Ok(())
+}
+
+/// Lists the stash, most recent first, and removes the picked entry
+fn pick_stashed(hooks: &Hooks) -> anyhow::Result<Option<StashedWindow>> {
+    let mut items = Array::from_iter(["Unstash window:"]);
+    let names = STASH.with_borrow(|stash| {
+        stash
+            .iter()
+            .rev()
+            .map(|stashed| {
+                stashed
+                    .buf
+                    .get_name()
+                    .map(|name| name.display().to_string())
+            })
+            .collect::<Result<Vec<_>, _>>()
+    });
+    let names = names.context("failed to get stashed buffer names")?;
+    if names.is_empty() {
+        notify_warn("[nvim_winpick] no stashed windows");
+        return Ok(None);
+    }
+    for (ind, name) in names.iter().enumerate() {
+        let name = if name.is_empty() { "[No Name]" } else { name };
+        items.push(format!("{}. {name}", ind + 1));
+    }
+    let picked: usize = nvim_oxi::api::call_function("inputlist", (items,))
+        .context("failed to prompt for stashed window")?;
+    // 0 is cancelled, out of range can be typed in
+    if picked == 0 || picked > names.len() {
+        hooks::cancelled(hooks);
+        return Ok(None);
+    }
+    Ok(STASH.with_borrow_mut(|stash| {
+        let ind = stash.len() - picked;
+        Some(stash.remove(ind))
+    }))
+}
+
+fn restore(stashed: &StashedWindow) -> anyhow::Result<()> {
+    if !stashed.buf.is_valid() {
+        bail!("the stashed window's buffer no longer exists");
+    }
+    if stashed.tab.is_valid() {
+        nvim_oxi::api::set_current_tabpage(&stashed.tab).context("failed to switch tab")?;
+    }
+    let mut window = match &stashed.placement {
+        Placement::Float(cfg) => nvim_oxi::api::open_win(&stashed.buf, true, cfg)
+            .context("failed to restore floating window")?,
+        Placement::Split(placement) => {
+            // If the neighbour is gone, splitting the current window is the best guess
+            let anchor = match &placement.anchor {
+                None => Window::from(-1),
+                Some(anchor) if anchor.is_valid() => anchor.clone(),
+                Some(_) => nvim_oxi::api::get_current_win(),
+            };
+            let mut window = nvim_oxi::api::open_win(
+                &stashed.buf,
+                true,
+                &split_config(anchor, placement.direction),
+            )
+            .context("failed to restore window")?;
+            match placement.direction {
+                SplitDirection::Left | SplitDirection::Right => window
+                    .set_width(stashed.width)
+                    .context("failed to restore window width")?,
+                SplitDirection::Above | SplitDirection::Below => window
+                    .set_height(stashed.height)
+                    .context("failed to restore window height")?,
+            }
+            window
+        }
+    };
+    let (row, col) = stashed.cursor;
+    // The buffer might have shrunk while stashed
+    let _ = window.set_cursor(row, col);
+    Ok(())
+}
@@ -0,0 +1,99 @@
+use anyhow::{bail, Context as _};
+use nvim_oxi::api::{TabPage, Window};
+
+use crate::ctx::Context;
+use crate::draw::{TabTarget, TablineDrawer};
+use crate::geometry::Positions;
+use crate::hooks;
+use crate::opts::{Opts, RelativeKeys};
+use crate::win::place_window_relative;
+
+pub(crate) fn pick_move_to_tab(
+    focus_new: bool,
+    relative_keys: Option<&RelativeKeys>,
+    opts: &Opts,
+    ctx: &mut Context,
+) -> anyhow::Result<()> {
+    let refocus = (!focus_new).then(|| ctx.get_current_win());
+    let Some(mut window) = super::pick_window(opts, ctx)? else {
+        return Ok(());
+    };
+    // Neither a new tab nor a split in another one can take a tab's last split
+    if let Some(reason) = super::close::last_window(&window)? {
+        bail!(
+            "can't move window {} out of its tab, {reason}",
+            window.handle()
+        );
+    }
+    let Some(target) = pick_tab(&opts.selection_chars)? else {
+        hooks::cancelled(&opts.hooks);
+        return Ok(());
+    };
+    match target {
+        TabTarget::New => {
+            nvim_oxi::api::set_current_win(&window).context("failed to focus window to move")?;
+            nvim_oxi::api::command("wincmd T").context("failed to move window to new tab")?;
+        }
+        TabTarget::Existing(tab) => {
+            nvim_oxi::api::set_current_tabpage(&tab).context("failed to switch tab")?;
+            let (mut target_win, pos) = if let Some(relative_keys) = relative_keys {
+                let Some(picked) = pick_relative_in_tab(relative_keys, opts)? else {
+                    // Cancelled, go back to where we started
+                    nvim_oxi::api::set_current_win(&ctx.get_current_win())
+                        .context("failed to return to old window")?;
+                    return Ok(());
+                };
+                picked
+            } else {
+                let target_win = tab.get_win().context("failed to get tab window")?;
+                (target_win, Positions::RightFullVer)
+            };
+            place_window_relative(&mut window, &mut target_win, pos)?;
+        }
+    }
+    if let Some(refocus) = refocus.filter(Window::is_valid) {
+        nvim_oxi::api::set_current_win(&refocus).context("failed to refocus old window")?;
+    } else {
+        nvim_oxi::api::set_current_win(&window).context("failed to focus moved window")?;
+    }
+    Ok(())
+}
+
+/// Picks a window and a position relative to it in the current tab
+fn pick_relative_in_tab(
+    relative_keys: &RelativeKeys,
+    opts: &Opts,
+) -> anyhow::Result<Option<(Window, Positions)>> {
+    // The current window has changed with the tab, so a fresh context is needed
+    let mut tab_ctx = Context::DEFAULT;
+    let Some(target_win) = super::pick_window(opts, &mut tab_ctx)? else {
+        return Ok(None);
+    };
+    let Some(pos) = super::win_relative::pick_relative_position(
+        &target_win,
+        relative_keys,
+        opts.relative_preview,
+        None,
+    )?
+    else {
+        hooks::cancelled(&opts.hooks);
+        return Ok(None);
+    };
+    Ok(Some((target_win, pos)))
+}
+
+/// Labels every other tabpage (and a new tab) on the tabline and waits for the user to pick one
+fn pick_tab(chars: &str) -> anyhow::Result<Option<TabTarget>> {
+    let current = nvim_oxi::api::get_current_tabpage();
+    let tabs: Vec<TabPage> = nvim_oxi::api::list_tabpages()
+        .filter(|tab| tab != &current)
+        .collect();
+    let mut drawer = TablineDrawer::new(chars, &tabs);
+    drawer.draw()?;
+    nvim_oxi::api::command("redraw").context("failed to redraw")?;
+    let ch: u32 = nvim_oxi::api::call_function("getchar", ((),)).context("failed to get char")?;
+    let ch = char::from_u32(ch).with_context(|| format!("invalid char picked: {ch}"))?;
+    drawer.clear()?;
+    nvim_oxi::api::command("redraw").context("failed to redraw")?;
+    Ok(drawer.target_for_char(ch))
+}
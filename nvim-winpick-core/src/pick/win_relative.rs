@@ -1,34 +1,219 @@
 use anyhow::Context as _;
+use nvim_oxi::api::Window;
 
-use crate::{draw::FloatingLetterDrawer, win::open_split_with};
+use crate::buf::BufSource;
+use crate::draw::{FloatingLetterDrawer, PickBetweenWindowSplits};
+use crate::geometry::{Direction, Positions};
+use crate::hooks;
+use crate::pick::PickedSize;
+use crate::repeat::{remember, Resolved};
+use crate::win::{
+    jump_to_location, open_split_with, place_window_relative, Location, SplitSize, SplitSizing,
+};
 
 use crate::ctx::Context;
-use crate::opts::Opts;
+use crate::opts::{Opts, RelativeKeys};
 
 pub(crate) fn pick_win_relative(
-    path: &str,
+    source: &BufSource,
+    location: Option<Location>,
+    sizing: SplitSizing,
     focus_new: bool,
-    relative_chars: &str,
+    relative_keys: &RelativeKeys,
     opts: &Opts,
     ctx: &mut Context,
 ) -> anyhow::Result<()> {
     let refocus = (!focus_new).then(|| ctx.get_current_win());
-    let Some(mut win) = crate::pick::pick_window(opts, ctx)? else {
+    let Some(win) = crate::pick::pick_window(opts, ctx)? else {
         return Ok(());
     };
     nvim_oxi::api::set_current_win(&win).context("failed to set focus window to picked window")?;
-    let mut drawer = FloatingLetterDrawer::new_draw_within(relative_chars, &win);
+    let Some(pos) =
+        pick_relative_position(&win, relative_keys, opts.relative_preview, sizing.size)?
+    else {
+        hooks::cancelled(&opts.hooks);
+        return Ok(());
+    };
+    let size = if pos.split_axis().is_some() {
+        let PickedSize::Size(size) = crate::pick::pick_split_size(sizing)? else {
+            hooks::cancelled(&opts.hooks);
+            return Ok(());
+        };
+        size
+    } else {
+        None
+    };
+    if let Some(refocus) = &refocus {
+        nvim_oxi::api::set_current_win(refocus).context("failed to refocus old window")?;
+    }
+    open_relative(source, location, size, win.clone(), pos, focus_new)?;
+    remember(
+        Resolved::OpenRelative {
+            source: source.clone(),
+            location,
+            size,
+            window: win,
+            position: pos,
+            focus_new,
+        },
+        opts.dot_repeat,
+    )
+}
+
+/// Opens `source` at `pos` relative to `win`, the split commands split the current window so
+/// `win` is focused for them
+pub(crate) fn open_relative(
+    source: &BufSource,
+    location: Option<Location>,
+    size: Option<SplitSize>,
+    mut win: Window,
+    pos: Positions,
+    focus_new: bool,
+) -> anyhow::Result<()> {
+    let refocus = (!focus_new).then(nvim_oxi::api::get_current_win);
+    nvim_oxi::api::set_current_win(&win).context("failed to focus window to split")?;
+    let mut new_win = open_split_with(source, refocus.as_ref(), &mut win, pos, size)?;
+    if let Some(location) = location {
+        jump_to_location(&mut new_win, location)?;
+    }
+
+    Ok(())
+}
+
+/// Moves `source` (or the current window) to `position` relative to `target`, picking the
+/// target and position if not supplied
+pub(crate) fn pick_place_window_relative(
+    source: Option<Window>,
+    target: Option<Window>,
+    position: Option<Positions>,
+    focus_new: bool,
+    relative_keys: &RelativeKeys,
+    opts: &Opts,
+    ctx: &mut Context,
+) -> anyhow::Result<()> {
+    // 0 is the current window in the api, but it's compared against other handles below
+    let source = match source {
+        Some(source) if source.handle() != 0 => source,
+        _ => ctx.get_current_win(),
+    };
+    let target = match target {
+        Some(target) if target.handle() != 0 => target,
+        Some(_) => ctx.get_current_win(),
+        None => {
+            let Some(target) = crate::pick::pick_window_matching(opts, ctx, |w| w != &source)?
+            else {
+                return Ok(());
+            };
+            target
+        }
+    };
+    let pos = if let Some(pos) = position {
+        pos
+    } else {
+        let Some(pos) =
+            pick_relative_position(&target, relative_keys, opts.relative_preview, None)?
+        else {
+            hooks::cancelled(&opts.hooks);
+            return Ok(());
+        };
+        pos
+    };
+    place_relative(source, target.clone(), pos, focus_new)?;
+    remember(
+        Resolved::PlaceRelative {
+            target,
+            position: pos,
+            focus_new,
+        },
+        opts.dot_repeat,
+    )
+}
+
+/// Moves `source` to `pos` relative to `target`
+pub(crate) fn place_relative(
+    mut source: Window,
+    mut target: Window,
+    pos: Positions,
+    focus_new: bool,
+) -> anyhow::Result<()> {
+    let refocus = (!focus_new).then(nvim_oxi::api::get_current_win);
+    place_window_relative(&mut source, &mut target, pos)?;
+    if let Some(refocus) = refocus.filter(Window::is_valid) {
+        nvim_oxi::api::set_current_win(&refocus).context("failed to refocus old window")?;
+    } else {
+        nvim_oxi::api::set_current_win(&source).context("failed to focus placed window")?;
+    }
+    Ok(())
+}
+
+/// Draws the relative position prompt within `win` and waits for the user to pick one of them.
+/// With `preview`, the picked position is hovered first, showing the region it would cover with
+/// a new split taking `size`, see [`hover_relative_position`]
+pub(crate) fn pick_relative_position(
+    win: &Window,
+    relative_keys: &RelativeKeys,
+    preview: bool,
+    size: Option<SplitSize>,
+) -> anyhow::Result<Option<Positions>> {
+    let mut drawer = FloatingLetterDrawer::new_draw_within(relative_keys, win);
     drawer.draw_multi()?;
+    if preview {
+        return hover_relative_position(&mut drawer, relative_keys, size);
+    }
     nvim_oxi::api::command("redraw").context("failed to redraw")?;
     let ch: u32 = nvim_oxi::api::call_function("getchar", ((),)).context("failed to get char")?;
     let ch = char::from_u32(ch).with_context(|| format!("invalid char picked: {ch}"))?;
     nvim_oxi::api::command("redraw").context("failed to redraw")?;
-    let pos = drawer.pos_for_char(ch);
-    let Some(pos) = pos else {
-        return Ok(());
-    };
-    drop(drawer);
-    open_split_with(path, refocus.as_ref(), &mut win, pos)?;
+    Ok(drawer.pos_for_char(ch))
+}
 
-    Ok(())
+const CONFIRM: char = '\r';
+const CTRL_H: char = '\u{8}';
+const CTRL_J: char = '\n';
+const CTRL_K: char = '\u{b}';
+const CTRL_L: char = '\u{c}';
+
+/// Lets the user move between positions while showing the region of the hovered one.
+/// `<C-h>`, `<C-j>`, `<C-k>` and `<C-l>` move to the closest position in that direction,
+/// a position's key hovers it, pressing it again or `<CR>` confirms, any other key cancels.
+/// Control keys are used for moving since the keys are often taken from `selection_chars`,
+/// which by default contain `j`, `k`, and `l`
+fn hover_relative_position(
+    drawer: &mut FloatingLetterDrawer<PickBetweenWindowSplits<'_>>,
+    relative_keys: &RelativeKeys,
+    size: Option<SplitSize>,
+) -> anyhow::Result<Option<Positions>> {
+    let Some((mut hovered, _)) = relative_keys.iter().next() else {
+        return Ok(None);
+    };
+    loop {
+        drawer.draw_preview(hovered, size)?;
+        nvim_oxi::api::command("redraw").context("failed to redraw")?;
+        let ch: u32 =
+            nvim_oxi::api::call_function("getchar", ((),)).context("failed to get char")?;
+        let ch = char::from_u32(ch).with_context(|| format!("invalid char picked: {ch}"))?;
+        let direction = match ch {
+            CONFIRM => break,
+            CTRL_H => Direction::Left,
+            CTRL_J => Direction::Down,
+            CTRL_K => Direction::Up,
+            CTRL_L => Direction::Right,
+            ch => match drawer.pos_for_char(ch) {
+                Some(pos) if pos == hovered => break,
+                Some(pos) => {
+                    hovered = pos;
+                    continue;
+                }
+                None => {
+                    nvim_oxi::api::command("redraw").context("failed to redraw")?;
+                    return Ok(None);
+                }
+            },
+        };
+        if let Some(pos) = drawer.neighbour(hovered, direction)? {
+            hovered = pos;
+        }
+    }
+    nvim_oxi::api::command("redraw").context("failed to redraw")?;
+    Ok(Some(hovered))
 }
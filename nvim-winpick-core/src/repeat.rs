@@ -0,0 +1,205 @@
+use std::cell::RefCell;
+
+use anyhow::{bail, Context};
+use nvim_oxi::api::Window;
+
+use crate::buf::BufSource;
+use crate::geometry::Positions;
+use crate::notify::notify_warn;
+use crate::pick::{simple_open, simple_operations, win_relative};
+use crate::win::{Location, SplitKind, SplitSize};
+
+/// An action with everything that was picked for it, so it can run again without picking
+#[derive(Debug, Clone)]
+pub(crate) enum Resolved {
+    Focus {
+        window: Window,
+    },
+    Swap {
+        window: Window,
+        focus_new: bool,
+    },
+    OpenSplit {
+        source: BufSource,
+        location: Option<Location>,
+        kind: SplitKind,
+        size: Option<SplitSize>,
+        window: Window,
+        focus_new: bool,
+    },
+    OpenOver {
+        source: BufSource,
+        location: Option<Location>,
+        window: Window,
+        focus_new: bool,
+    },
+    OpenRelative {
+        source: BufSource,
+        location: Option<Location>,
+        size: Option<SplitSize>,
+        window: Window,
+        position: Positions,
+        focus_new: bool,
+    },
+    /// Moves whichever window is current when it runs
+    PlaceRelative {
+        target: Window,
+        position: Positions,
+        focus_new: bool,
+    },
+}
+
+impl Resolved {
+    pub(crate) fn run(&self) -> anyhow::Result<()> {
+        let (Self::Focus { window }
+        | Self::Swap { window, .. }
+        | Self::OpenSplit { window, .. }
+        | Self::OpenOver { window, .. }
+        | Self::OpenRelative { window, .. }
+        | Self::PlaceRelative { target: window, .. }) = self;
+        if !window.is_valid() {
+            bail!("window {} no longer exists", window.handle());
+        }
+        match self {
+            Self::Focus { window } => {
+                nvim_oxi::api::set_current_win(window).context("failed to focus window")?;
+                Ok(())
+            }
+            Self::Swap { window, focus_new } => {
+                simple_operations::swap_with(window.clone(), *focus_new)
+            }
+            Self::OpenSplit {
+                source,
+                location,
+                kind,
+                size,
+                window,
+                focus_new,
+            } => simple_open::split_at_win(
+                source,
+                *location,
+                *kind,
+                *size,
+                window.clone(),
+                *focus_new,
+            ),
+            Self::OpenOver {
+                source,
+                location,
+                window,
+                focus_new,
+            } => simple_open::open_over(source, *location, window.clone(), *focus_new),
+            Self::OpenRelative {
+                source,
+                location,
+                size,
+                window,
+                position,
+                focus_new,
+            } => win_relative::open_relative(
+                source,
+                *location,
+                *size,
+                window.clone(),
+                *position,
+                *focus_new,
+            ),
+            Self::PlaceRelative {
+                target,
+                position,
+                focus_new,
+            } => win_relative::place_relative(
+                nvim_oxi::api::get_current_win(),
+                target.clone(),
+                *position,
+                *focus_new,
+            ),
+        }
+    }
+
+    fn with_source(mut self, new: BufSource) -> anyhow::Result<Self> {
+        match &mut self {
+            Self::OpenSplit { source, .. }
+            | Self::OpenOver { source, .. }
+            | Self::OpenRelative { source, .. } => *source = new,
+            Self::Focus { .. } | Self::Swap { .. } | Self::PlaceRelative { .. } => {
+                bail!("the last action doesn't open a buffer, 'path' and 'buf' can't be used");
+            }
+        }
+        Ok(self)
+    }
+}
+
+thread_local! {
+    /// Neovim calls into the plugin from a single thread
+    static LAST: RefCell<Option<Resolved>> = const { RefCell::new(None) };
+    /// The actions [`not_repeatable`] told about already
+    static REPORTED: RefCell<Vec<&'static str>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Runs the first `g@_`, which only makes it the change that `.` repeats
+const PRIME_OPERATORFUNC: &str = "v:lua.require'nvim_winpick'.prime_repeat";
+const REPEAT_OPERATORFUNC: &str = "v:lua.require'nvim_winpick'.repeat_operator";
+
+/// Stores `action` as the last one, with `dot_repeat`, `.` will repeat it
+pub(crate) fn remember(action: Resolved, dot_repeat: bool) -> anyhow::Result<()> {
+    LAST.set(Some(action));
+    if !dot_repeat {
+        return Ok(());
+    }
+    // Fed keys would be typed into the buffer in other modes
+    let mode: String = nvim_oxi::api::call_function("mode", ((),)).context("failed to get mode")?;
+    if mode != "n" {
+        return Ok(());
+    }
+    set_operatorfunc(PRIME_OPERATORFUNC)?;
+    // Queued, it runs once the current mapping or call returns. `_` stays on the current line,
+    // unlike `l` it can't fail in an empty buffer, which would leave `.` repeating whatever it
+    // repeated before
+    nvim_oxi::api::call_function::<_, i64>("feedkeys", ("g@_", "n"))
+        .context("failed to make action repeatable")?;
+    Ok(())
+}
+
+/// With `dot_repeat`, tells once that `.` doesn't repeat `action`, only the actions that
+/// [`remember`] themselves can run again
+pub(crate) fn not_repeatable(action: &'static str, dot_repeat: bool) {
+    if !dot_repeat || REPORTED.with_borrow(|reported| reported.contains(&action)) {
+        return;
+    }
+    REPORTED.with_borrow_mut(|reported| reported.push(action));
+    notify_warn(&format!(
+        "[nvim_winpick] `.` doesn't repeat {action}, only focus, swap, split, over, relative and place"
+    ));
+}
+
+fn set_operatorfunc(func: &str) -> anyhow::Result<()> {
+    nvim_oxi::api::set_option_value(
+        "operatorfunc",
+        func,
+        &nvim_oxi::api::opts::OptionOpts::default(),
+    )
+    .context("failed to set operatorfunc")
+}
+
+/// Called through `operatorfunc` by the `g@_` fed by [`remember`], from here on `.` repeats
+/// the last action
+pub(crate) fn prime_repeat() -> anyhow::Result<()> {
+    set_operatorfunc(REPEAT_OPERATORFUNC)
+}
+
+/// Runs the last action again, opening `source` instead if supplied
+pub(crate) fn repeat_last(source: Option<BufSource>) -> anyhow::Result<()> {
+    let Some(mut action) = LAST.with_borrow(Clone::clone) else {
+        bail!("no action to repeat");
+    };
+    if let Some(source) = source {
+        action = action.with_source(source)?;
+    }
+    action.run()
+}
+
+/// Called through `operatorfunc` by `.`
+pub(crate) fn repeat_operator() -> anyhow::Result<()> {
+    repeat_last(None)
+}
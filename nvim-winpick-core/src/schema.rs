@@ -0,0 +1,239 @@
+use std::fmt::Write as _;
+
+use anyhow::bail;
+use schemars::generate::SchemaSettings;
+use schemars::transform::{RecursiveTransform, Transform};
+use schemars::{json_schema, JsonSchema, Schema, SchemaGenerator};
+use serde_json::{Map, Value};
+
+use crate::opts::{
+    CloseFields, DockFloatFields, MoveToTabFields, OpenManyFields, OpenOverFields,
+    OpenRelativeFields, OpenSplitFields, OptsFields, PlaceRelativeFields, RepeatFields,
+    SetupFields, UnstashFields,
+};
+
+/// The root of the JSON schema, the fields of [`OptsFields`] and [`SetupFields`] in one table
+const ROOT: &str = "SetupOpts";
+
+/// The opts of the functions, which end up in the schema next to what `setup` takes
+const FUNCTION_OPTS: [fn(&mut SchemaGenerator) -> Schema; 10] = [
+    SchemaGenerator::subschema_for::<OpenSplitFields>,
+    SchemaGenerator::subschema_for::<OpenOverFields>,
+    SchemaGenerator::subschema_for::<OpenRelativeFields>,
+    SchemaGenerator::subschema_for::<CloseFields>,
+    SchemaGenerator::subschema_for::<UnstashFields>,
+    SchemaGenerator::subschema_for::<RepeatFields>,
+    SchemaGenerator::subschema_for::<PlaceRelativeFields>,
+    SchemaGenerator::subschema_for::<OpenManyFields>,
+    SchemaGenerator::subschema_for::<DockFloatFields>,
+    SchemaGenerator::subschema_for::<MoveToTabFields>,
+];
+
+/// Types JSON has nothing to describe with, by their name in the schema, and their lua type
+const LUA_ONLY: [(&str, &str); 1] = [("Hook", "fun(event: table)")];
+
+/// The committed copies are `lua/nvim_winpick/types.lua` and `nvim_winpick.schema.json`
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum Format {
+    Json,
+    Lua,
+}
+
+impl Format {
+    pub(crate) fn from_str(s: &str) -> anyhow::Result<Self> {
+        match s {
+            "json" => Ok(Self::Json),
+            "lua" => Ok(Self::Lua),
+            other => bail!("unknown format '{other}', expected 'json' or 'lua'"),
+        }
+    }
+}
+
+/// Derived from the same serde types the opts are parsed with, so it can't drift from them
+pub(crate) fn generate(format: Format) -> String {
+    let defs = definitions();
+    match format {
+        Format::Json => json_schema(&defs),
+        Format::Lua => lua_annotations(&defs),
+    }
+}
+
+/// Every named schema, the root first
+fn definitions() -> Map<String, Value> {
+    let mut generator = SchemaSettings::draft2020_12()
+        .with_transform(RecursiveTransform(tidy))
+        .into_generator();
+    // Only schemas the generator hands out are transformed
+    let mut root = merged::<OptsFields, SetupFields>(&mut generator);
+    RecursiveTransform(tidy).transform(&mut root);
+    for opts in FUNCTION_OPTS {
+        opts(&mut generator);
+    }
+    let mut defs = Map::new();
+    defs.insert(ROOT.to_string(), root.to_value());
+    defs.extend(generator.take_definitions(true));
+    defs
+}
+
+/// Fields of [`OptsFields`] that are only taken per call, not as defaults
+const PER_CALL: [&str; 1] = ["count"];
+
+/// `A`'s schema, with the fields of `B` added, as defaults, so without the [`PER_CALL`] fields
+pub(crate) fn merged<A: JsonSchema, B: JsonSchema>(generator: &mut SchemaGenerator) -> Schema {
+    let mut schema = A::json_schema(generator);
+    let other = B::json_schema(generator);
+    if let (Some(Value::Object(properties)), Some(Value::Object(more))) =
+        (schema.get_mut("properties"), other.get("properties"))
+    {
+        properties.extend(more.clone());
+        properties.retain(|field, _| !PER_CALL.contains(&field.as_str()));
+    }
+    schema
+}
+
+/// Doc comments are written for the code, not the config, and an enum's values are only split
+/// up to carry theirs. Lua tables can't hold nil, so optional fields don't take null either,
+/// they're left out instead
+fn tidy(schema: &mut Schema) {
+    schema.remove("title");
+    schema.remove("description");
+    if let Some(Value::Array(types)) = schema.get_mut("type") {
+        types.retain(|ty| ty != "null");
+        if types.len() == 1 {
+            let ty = types.remove(0);
+            schema.insert("type".to_string(), ty);
+        }
+    }
+    if let Some(Value::Array(alternatives)) = schema.get_mut("anyOf") {
+        alternatives.retain(|alt| alt.get("type").is_none_or(|ty| ty != "null"));
+        if let [Value::Object(only)] = alternatives.as_slice() {
+            let only = only.clone();
+            schema.remove("anyOf");
+            schema.ensure_object().extend(only);
+        }
+    }
+    let values = schema
+        .get("oneOf")
+        .and_then(Value::as_array)
+        .and_then(|variants| variants.iter().map(values).collect::<Option<Vec<_>>>());
+    if let Some(values) = values {
+        schema.remove("oneOf");
+        schema.insert("type".to_string(), "string".into());
+        schema.insert("enum".to_string(), values.concat().into());
+    }
+}
+
+/// The values of a schema that only takes fixed values
+fn values(schema: &Value) -> Option<Vec<Value>> {
+    if let Some(value) = schema.get("const") {
+        return Some(vec![value.clone()]);
+    }
+    schema.get("enum").and_then(Value::as_array).cloned()
+}
+
+fn json_schema(defs: &Map<String, Value>) -> String {
+    let schema = json_schema!({
+        "$schema": "https://json-schema.org/draft/2020-12/schema",
+        "title": "nvim_winpick opts",
+        "$ref": format!("#/$defs/{ROOT}"),
+        "$defs": defs,
+    });
+    let mut out = serde_json::to_string_pretty(&schema).unwrap_or_default();
+    out.push('\n');
+    out
+}
+
+/// Tables with fields become classes, anything else an alias
+fn lua_annotations(defs: &Map<String, Value>) -> String {
+    let mut out = String::from(
+        "---@meta\n\
+         -- Generated with `require(\"nvim_winpick\").schema(\"lua\")`, don't edit by hand\n",
+    );
+    for (name, schema) in defs {
+        if let Some(properties) = schema.get("properties").and_then(Value::as_object) {
+            let _ = write!(out, "\n---@class nvim_winpick.{name}\n");
+            let required = schema.get("required").and_then(Value::as_array);
+            for (field, schema) in properties {
+                let optional = if required.is_some_and(|req| req.contains(&field.as_str().into())) {
+                    ""
+                } else {
+                    "?"
+                };
+                let _ = writeln!(out, "---@field {field}{optional} {}", lua_type(schema));
+            }
+        } else {
+            let lua = LUA_ONLY
+                .iter()
+                .find(|(only, _)| only == name)
+                .map_or_else(|| lua_type(schema), |(_, lua)| (*lua).to_string());
+            let _ = write!(out, "\n---@alias nvim_winpick.{name} {lua}\n");
+        }
+    }
+    out
+}
+
+fn lua_type(schema: &Value) -> String {
+    if let Some(reference) = schema.get("$ref").and_then(Value::as_str) {
+        let name = reference.trim_start_matches("#/$defs/");
+        return format!("nvim_winpick.{name}");
+    }
+    if let Some(values) = values(schema) {
+        return values
+            .iter()
+            .map(Value::to_string)
+            .collect::<Vec<_>>()
+            .join("|");
+    }
+    if let Some(alternatives) = schema
+        .get("anyOf")
+        .or_else(|| schema.get("oneOf"))
+        .and_then(Value::as_array)
+    {
+        return union(alternatives.iter().map(lua_type));
+    }
+    match schema.get("type") {
+        Some(Value::Array(types)) => union(
+            types
+                .iter()
+                .map(|ty| lua_type(&json_schema!({ "type": ty }).to_value())),
+        ),
+        Some(Value::String(ty)) => match ty.as_str() {
+            "boolean" => "boolean".to_string(),
+            "integer" => "integer".to_string(),
+            "number" => "number".to_string(),
+            "string" => "string".to_string(),
+            "array" => {
+                let item = schema
+                    .get("items")
+                    .map_or_else(|| "any".to_string(), lua_type);
+                if item.contains('|') {
+                    format!("({item})[]")
+                } else {
+                    format!("{item}[]")
+                }
+            }
+            "object" => match schema.get("additionalProperties") {
+                Some(value @ Value::Object(_)) => {
+                    let key = schema
+                        .get("propertyNames")
+                        .map_or_else(|| "string".to_string(), lua_type);
+                    format!("table<{key}, {}>", lua_type(value))
+                }
+                _ => "table".to_string(),
+            },
+            _ => "any".to_string(),
+        },
+        _ => "any".to_string(),
+    }
+}
+
+/// The alternatives joined, same ones only once
+fn union(alternatives: impl Iterator<Item = String>) -> String {
+    let mut seen: Vec<String> = Vec::new();
+    for alternative in alternatives {
+        if !seen.contains(&alternative) {
+            seen.push(alternative);
+        }
+    }
+    seen.join("|")
+}
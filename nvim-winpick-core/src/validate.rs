@@ -0,0 +1,586 @@
+use std::fmt::{self, Write as _};
+
+use nvim_oxi::conversion::FromObject;
+use nvim_oxi::{Array, Dictionary, Function, Object, ObjectKind};
+use schemars::{JsonSchema, SchemaGenerator};
+use serde::de::{
+    self, DeserializeOwned, DeserializeSeed, IntoDeserializer, MapAccess, SeqAccess, Visitor,
+};
+
+/// Every problem found in an opts table, each with the path of the offending key
+pub(crate) struct Issues {
+    name: &'static str,
+    issues: Vec<(String, String)>,
+}
+
+impl fmt::Display for Issues {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid '{}':", self.name)?;
+        for (path, issue) in &self.issues {
+            if path.is_empty() {
+                write!(f, "\n  {issue}")?;
+            } else {
+                write!(f, "\n  `{path}`: {issue}")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+// Errors are notified with `{:#?}`, which would print the list on one line, escaped
+impl fmt::Debug for Issues {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(self, f)
+    }
+}
+
+impl std::error::Error for Issues {}
+
+impl Issues {
+    pub(crate) fn new(name: &'static str) -> Self {
+        Self {
+            name,
+            issues: Vec::new(),
+        }
+    }
+
+    /// Records `issue` at `path`, unless it's about a value that has a problem recorded already
+    pub(crate) fn push(&mut self, path: impl Into<String>, issue: impl Into<String>) {
+        let path = path.into();
+        if !self
+            .issues
+            .iter()
+            .any(|(reported, _)| within(reported, &path))
+        {
+            self.issues.push((path, issue.into()));
+        }
+    }
+
+    pub(crate) fn into_result(self) -> Result<(), Self> {
+        if self.issues.is_empty() {
+            Ok(())
+        } else {
+            Err(self)
+        }
+    }
+
+    /// Deserializes `object` found at `path`, `None` if anything was wrong with it
+    pub(crate) fn deserialize<T: DeserializeOwned>(
+        &mut self,
+        object: Object,
+        path: &str,
+    ) -> Option<T> {
+        let recorded = self.issues.len();
+        let value = self.deserialize_rest(object, path)?;
+        (self.issues.len() == recorded).then_some(value)
+    }
+
+    /// Deserializes `object` found at `path`, nil is the same as an empty table. Every problem
+    /// is recorded, not just the first one serde stops at: the value it failed on is skipped and
+    /// the rest deserialized again. That rest is returned, unless the problem is with `object`
+    /// as a whole
+    pub(crate) fn deserialize_rest<T: DeserializeOwned>(
+        &mut self,
+        object: Object,
+        path: &str,
+    ) -> Option<T> {
+        let object = if object.kind() == ObjectKind::Nil {
+            Dictionary::new().into()
+        } else {
+            object
+        };
+        let kind = object.kind();
+        let recorded = self.issues.len();
+        let mut skipped = Vec::new();
+        loop {
+            let deserializer = Deserializer {
+                object: object.clone(),
+                path: path.to_string(),
+                skipped: &skipped,
+            };
+            let error = match T::deserialize(deserializer) {
+                Ok(value) => return Some(value),
+                Err(error) => error.at(path, kind),
+            };
+            let at = error.at.unwrap_or_default();
+            // Checks of the whole value see the skipped parts missing, which isn't the problem
+            if !self.issues[recorded..]
+                .iter()
+                .any(|(reported, _)| within(reported, &at))
+            {
+                let issue = error.issue.to_string();
+                self.issues.push((at.clone(), issue));
+            }
+            if at == path || skipped.contains(&at) {
+                return None;
+            }
+            skipped.push(at);
+        }
+    }
+}
+
+/// Deserializes one of the opts types, reporting every problem with its path
+pub(crate) fn deserialize<T: DeserializeOwned>(
+    object: Object,
+    name: &'static str,
+) -> Result<T, Issues> {
+    let mut issues = Issues::new(name);
+    match issues.deserialize(object, "") {
+        Some(value) => Ok(value),
+        None => Err(issues),
+    }
+}
+
+/// The keys `T` takes, as its schema lists them
+pub(crate) fn field_names<T: JsonSchema>() -> Vec<String> {
+    SchemaGenerator::default()
+        .into_root_schema_for::<T>()
+        .get("properties")
+        .and_then(serde_json::Value::as_object)
+        .map(|properties| properties.keys().cloned().collect())
+        .unwrap_or_default()
+}
+
+/// Whether `inner` is `outer` or a path below it
+fn within(inner: &str, outer: &str) -> bool {
+    outer.is_empty()
+        || inner
+            .strip_prefix(outer)
+            .is_some_and(|rest| rest.is_empty() || rest.starts_with('.') || rest.starts_with('['))
+}
+
+/// A deserialization error with the path of the value it's about, once it's known
+#[derive(Debug)]
+pub(crate) struct Error {
+    at: Option<String>,
+    issue: Issue,
+}
+
+#[derive(Debug)]
+enum Issue {
+    /// What was expected, the value's type is added once it's located
+    Mismatch(String),
+    Other(String),
+}
+
+impl fmt::Display for Issue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Mismatch(expected) => write!(f, "expected {expected}"),
+            Self::Other(issue) => f.write_str(issue),
+        }
+    }
+}
+
+impl Error {
+    fn mismatch(expected: impl Into<String>) -> Self {
+        Self {
+            at: None,
+            issue: Issue::Mismatch(expected.into()),
+        }
+    }
+
+    fn other(issue: impl Into<String>) -> Self {
+        Self {
+            at: None,
+            issue: Issue::Other(issue.into()),
+        }
+    }
+
+    /// Locates the error at `path` where the value is of type `found`, unless a deeper value
+    /// already claimed it
+    fn at(self, path: &str, found: ObjectKind) -> Self {
+        if self.at.is_some() {
+            return self;
+        }
+        let issue = match self.issue {
+            Issue::Mismatch(expected) => {
+                Issue::Other(format!("expected {expected}, got {}", found.as_static()))
+            }
+            other @ Issue::Other(_) => other,
+        };
+        Self {
+            at: Some(path.to_string()),
+            issue,
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.at {
+            Some(at) if !at.is_empty() => write!(f, "`{at}`: {}", self.issue),
+            _ => self.issue.fmt(f),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl de::Error for Error {
+    fn custom<T: fmt::Display>(msg: T) -> Self {
+        Self::other(msg.to_string())
+    }
+
+    fn invalid_type(_: de::Unexpected<'_>, exp: &dyn de::Expected) -> Self {
+        Self::mismatch(exp.to_string())
+    }
+
+    fn unknown_field(field: &str, expected: &'static [&'static str]) -> Self {
+        let mut issue = "unknown field".to_string();
+        if let Some(close) = closest(field, expected.iter().copied()) {
+            let _ = write!(issue, ", did you mean `{close}`?");
+        }
+        Self::other(issue)
+    }
+
+    fn unknown_variant(variant: &str, expected: &'static [&'static str]) -> Self {
+        let mut issue = format!("unknown value '{variant}'");
+        if let Some(close) = closest(variant, expected.iter().copied()) {
+            let _ = write!(issue, ", did you mean '{close}'?");
+        } else {
+            let _ = write!(issue, ", expected one of [{}]", expected.join(", "));
+        }
+        Self::other(issue)
+    }
+}
+
+/// Deserializes an object the way nvim-oxi's deserializer does, but knows where in the opts
+/// table it is and leaves out the values that were already reported
+struct Deserializer<'a> {
+    object: Object,
+    path: String,
+    skipped: &'a [String],
+}
+
+impl<'a> Deserializer<'a> {
+    fn child(&self, object: Object, path: String) -> Deserializer<'a> {
+        Deserializer {
+            object,
+            path,
+            skipped: self.skipped,
+        }
+    }
+
+    fn integer(self) -> Result<i64, Error> {
+        i64::from_object(self.object).map_err(|_| Error::mismatch("an integer"))
+    }
+
+    fn string(self) -> Result<String, Error> {
+        String::from_object(self.object).map_err(|_| Error::mismatch("a string"))
+    }
+
+    /// An empty lua table is an array
+    fn dict(self) -> Result<(Dictionary, Self), Error> {
+        let dict = match self.object.kind() {
+            ObjectKind::Dictionary => Dictionary::from_object(self.object.clone()).ok(),
+            ObjectKind::Array => Array::from_object(self.object.clone())
+                .ok()
+                .filter(Array::is_empty)
+                .map(|_| Dictionary::new()),
+            _ => None,
+        };
+        dict.map(|dict| (dict, self))
+            .ok_or_else(|| Error::mismatch("a table"))
+    }
+}
+
+impl<'de> de::Deserializer<'de> for Deserializer<'_> {
+    type Error = Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        match self.object.kind() {
+            ObjectKind::Nil => visitor.visit_unit(),
+            ObjectKind::Boolean => self.deserialize_bool(visitor),
+            ObjectKind::Float => self.deserialize_f64(visitor),
+            ObjectKind::String => self.deserialize_string(visitor),
+            ObjectKind::Array => self.deserialize_seq(visitor),
+            ObjectKind::Dictionary => self.deserialize_map(visitor),
+            ObjectKind::LuaRef => self.deserialize_f32(visitor),
+            _ => self.deserialize_i64(visitor),
+        }
+    }
+
+    fn deserialize_bool<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        let b = bool::from_object(self.object).map_err(|_| Error::mismatch("a boolean"))?;
+        visitor.visit_bool(b)
+    }
+
+    fn deserialize_i64<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_i64(self.integer()?)
+    }
+
+    fn deserialize_i8<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        self.deserialize_i64(visitor)
+    }
+
+    fn deserialize_i16<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        self.deserialize_i64(visitor)
+    }
+
+    fn deserialize_i32<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        self.deserialize_i64(visitor)
+    }
+
+    fn deserialize_u8<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        self.deserialize_i64(visitor)
+    }
+
+    fn deserialize_u16<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        self.deserialize_i64(visitor)
+    }
+
+    fn deserialize_u32<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        self.deserialize_i64(visitor)
+    }
+
+    fn deserialize_u64<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        self.deserialize_i64(visitor)
+    }
+
+    /// Functions come through as their registry reference in an `f32`, which is how nvim-oxi's
+    /// `Function` deserializes
+    fn deserialize_f32<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        let fun = Function::<(), ()>::from_object(self.object)
+            .map_err(|_| Error::mismatch("a function"))?;
+        #[allow(clippy::cast_precision_loss)]
+        visitor.visit_f32(fun.lua_ref() as f32)
+    }
+
+    fn deserialize_f64<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        let n = f64::from_object(self.object).map_err(|_| Error::mismatch("a float"))?;
+        visitor.visit_f64(n)
+    }
+
+    fn deserialize_char<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        let s = String::from_object(self.object).map_err(|_| Error::mismatch("a single char"))?;
+        let mut chars = s.chars();
+        match (chars.next(), chars.next()) {
+            (Some(ch), None) => visitor.visit_char(ch),
+            _ => Err(Error::other(format!("expected a single char, got '{s}'"))),
+        }
+    }
+
+    fn deserialize_str<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        self.deserialize_string(visitor)
+    }
+
+    fn deserialize_string<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_string(self.string()?)
+    }
+
+    fn deserialize_bytes<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        self.deserialize_string(visitor)
+    }
+
+    fn deserialize_byte_buf<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        self.deserialize_string(visitor)
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        if self.object.kind() == ObjectKind::Nil {
+            visitor.visit_none()
+        } else {
+            visitor.visit_some(self)
+        }
+    }
+
+    fn deserialize_unit<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        if self.object.kind() == ObjectKind::Nil {
+            visitor.visit_unit()
+        } else {
+            Err(Error::mismatch("nil"))
+        }
+    }
+
+    fn deserialize_unit_struct<V: Visitor<'de>>(
+        self,
+        _: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        self.deserialize_unit(visitor)
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_seq<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        // An empty table that came through as a dict is still an empty list
+        let items = match self.object.kind() {
+            ObjectKind::Array => Array::from_object(self.object.clone()).ok(),
+            ObjectKind::Dictionary => Dictionary::from_object(self.object.clone())
+                .ok()
+                .filter(Dictionary::is_empty)
+                .map(|_| Array::new()),
+            _ => None,
+        };
+        let items = items.ok_or_else(|| Error::mismatch("a list"))?;
+        visitor.visit_seq(Seq {
+            items: items
+                .into_iter()
+                .collect::<Vec<_>>()
+                .into_iter()
+                .enumerate(),
+            parent: self,
+        })
+    }
+
+    fn deserialize_tuple<V: Visitor<'de>>(self, _: usize, visitor: V) -> Result<V::Value, Error> {
+        self.deserialize_seq(visitor)
+    }
+
+    fn deserialize_tuple_struct<V: Visitor<'de>>(
+        self,
+        _: &'static str,
+        _: usize,
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        self.deserialize_seq(visitor)
+    }
+
+    fn deserialize_map<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        let (dict, parent) = self.dict()?;
+        visitor.visit_map(Map {
+            entries: dict.into_iter().collect::<Vec<_>>().into_iter(),
+            value: None,
+            parent,
+        })
+    }
+
+    fn deserialize_struct<V: Visitor<'de>>(
+        self,
+        _: &'static str,
+        _: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        self.deserialize_map(visitor)
+    }
+
+    /// Only plain values, `"<variant>"`
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _: &'static str,
+        variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        let variant = String::from_object(self.object)
+            .map_err(|_| Error::mismatch(format!("one of [{}]", variants.join(", "))))?;
+        visitor.visit_enum(variant.into_deserializer())
+    }
+
+    fn deserialize_identifier<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        self.deserialize_string(visitor)
+    }
+
+    fn deserialize_ignored_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_unit()
+    }
+}
+
+struct Seq<'a> {
+    items: std::iter::Enumerate<std::vec::IntoIter<Object>>,
+    parent: Deserializer<'a>,
+}
+
+impl<'de> SeqAccess<'de> for Seq<'_> {
+    type Error = Error;
+
+    fn next_element_seed<T: DeserializeSeed<'de>>(
+        &mut self,
+        seed: T,
+    ) -> Result<Option<T::Value>, Error> {
+        for (i, object) in self.items.by_ref() {
+            let path = format!("{}[{}]", self.parent.path, i + 1);
+            if self.parent.skipped.contains(&path) {
+                continue;
+            }
+            let kind = object.kind();
+            return seed
+                .deserialize(self.parent.child(object, path.clone()))
+                .map(Some)
+                .map_err(|e| e.at(&path, kind));
+        }
+        Ok(None)
+    }
+}
+
+struct Map<'a> {
+    entries: std::vec::IntoIter<(nvim_oxi::String, Object)>,
+    /// The value of the key that was just deserialized, with its path
+    value: Option<(Object, String)>,
+    parent: Deserializer<'a>,
+}
+
+impl<'de> MapAccess<'de> for Map<'_> {
+    type Error = Error;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(
+        &mut self,
+        seed: K,
+    ) -> Result<Option<K::Value>, Error> {
+        for (key, object) in self.entries.by_ref() {
+            let key = key.to_string_lossy().into_owned();
+            let path = key_path(&self.parent.path, &key);
+            if self.parent.skipped.contains(&path) {
+                continue;
+            }
+            self.value = Some((object, path.clone()));
+            return seed
+                .deserialize(self.parent.child(key.into(), path.clone()))
+                .map(Some)
+                .map_err(|e| e.at(&path, ObjectKind::String));
+        }
+        Ok(None)
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(&mut self, seed: V) -> Result<V::Value, Error> {
+        let (object, path) = self
+            .value
+            .take()
+            .ok_or_else(|| Error::other("a value without a key"))?;
+        let kind = object.kind();
+        seed.deserialize(self.parent.child(object, path.clone()))
+            .map_err(|e| e.at(&path, kind))
+    }
+}
+
+/// The path of `key` in the table at `parent`
+pub(crate) fn key_path(parent: &str, key: &str) -> String {
+    if parent.is_empty() {
+        key.to_string()
+    } else {
+        format!("{parent}.{key}")
+    }
+}
+
+/// The candidate closest to `typo`, if it's close enough to likely be what was meant
+pub(crate) fn closest<'a>(
+    typo: &str,
+    candidates: impl Iterator<Item = &'a str>,
+) -> Option<&'a str> {
+    let max = (typo.chars().count() / 3).max(2);
+    candidates
+        .map(|candidate| (edit_distance(typo, candidate), candidate))
+        .filter(|(distance, _)| *distance <= max)
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, candidate)| candidate)
+}
+
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut prev: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut cur = vec![i + 1; b.len() + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let substitute = prev[j] + usize::from(ca != *cb);
+            cur[j + 1] = substitute.min(prev[j + 1] + 1).min(cur[j] + 1);
+        }
+        prev = cur;
+    }
+    prev[b.len()]
+}
@@ -1,7 +1,15 @@
-use anyhow::{Context, Result};
-use nvim_oxi::api::Window;
+use std::borrow::Cow;
 
-use crate::geometry::Positions;
+use anyhow::{bail, Context, Result};
+use nvim_oxi::api::{
+    opts::{OptionOpts, SetExtmarkOptsBuilder},
+    types::{SplitDirection, WindowBorder, WindowConfig, WindowRelativeTo},
+    Buffer, Window,
+};
+use schemars::{json_schema, JsonSchema, Schema, SchemaGenerator};
+
+use crate::buf::BufSource;
+use crate::geometry::{Positions, SplitAxis, WindowDimensions};
 
 pub(crate) fn get_windows<F: FnMut(&Window) -> bool>(filter_fn: F) -> Result<Vec<Window>> {
     let tab = nvim_oxi::api::get_current_tabpage();
@@ -9,53 +17,394 @@ pub(crate) fn get_windows<F: FnMut(&Window) -> bool>(filter_fn: F) -> Result<Vec
     Ok(windows)
 }
 
-pub(crate) fn open_split_with(
-    path: &str,
-    keep_focus_at: Option<&Window>,
+pub(crate) fn is_floating(window: &Window) -> bool {
+    window.get_config().is_ok_and(|cfg| cfg.relative.is_some())
+}
+
+/// How much of the editor a window turned into a float should cover, in percent
+const FLOAT_SIZE_PERCENT: u32 = 80;
+
+/// Turns `window` into a floating window centered in the editor.
+/// The window itself is kept, so its buffer, cursor and view are kept as well.
+pub(crate) fn float_window(window: &mut Window) -> Result<()> {
+    let columns: u32 = nvim_oxi::api::get_option_value("columns", &OptionOpts::default())
+        .context("failed to get editor width")?;
+    let lines: u32 = nvim_oxi::api::get_option_value("lines", &OptionOpts::default())
+        .context("failed to get editor height")?;
+    let width = (columns * FLOAT_SIZE_PERCENT / 100).max(1);
+    let height = (lines * FLOAT_SIZE_PERCENT / 100).max(1);
+    let cfg = WindowConfig::builder()
+        .relative(WindowRelativeTo::Editor)
+        .width(width)
+        .height(height)
+        .row(f64::from(lines.saturating_sub(height) / 2))
+        .col(f64::from(columns.saturating_sub(width) / 2))
+        .border(WindowBorder::Rounded)
+        .build();
+    window
+        .set_config(&cfg)
+        .context("failed to turn window into a float")?;
+    Ok(())
+}
+
+/// Moves `window` to `pos` relative to `target`, the window is kept, so this works for
+/// floating windows and splits alike, even across tabpages.
+/// Placing at [`Positions::Center`] shows the window's buffer in `target` and closes `window`.
+pub(crate) fn place_window_relative(
     window: &mut Window,
+    target: &mut Window,
     pos: Positions,
 ) -> Result<()> {
-    let with_pos = match pos {
-        Positions::TopFullHor => {
-            format!("topleft split {path}")
-        }
-        Positions::LeftFullVer => {
-            format!("topleft vertical split {path}")
-        }
-        Positions::BotFullHor => {
-            format!("botright split {path}")
-        }
-        Positions::RightFullVer => {
-            format!("botright vertical split {path}")
-        }
-        Positions::Center => {
-            let buf = crate::buf::load_file_to_hidden_buffer(path)?;
-            window.set_buf(&buf).context("failed to set buffer")?;
-            if let Some(refocus) = keep_focus_at {
-                nvim_oxi::api::set_current_win(refocus).context("failed to refocus old window")?;
-            } else {
-                nvim_oxi::api::set_current_win(window).context("failed to focus new window")?;
-            }
+    if !window.is_valid() {
+        bail!("window to place is not valid");
+    }
+    if !target.is_valid() {
+        bail!("target window is not valid");
+    }
+    // -1 splits at the top level of the current tabpage, making the split full-width/height
+    let cfg = match pos {
+        Positions::TopFullHor => split_config(Window::from(-1), SplitDirection::Above),
+        Positions::RightFullVer => split_config(Window::from(-1), SplitDirection::Right),
+        Positions::BotFullHor => split_config(Window::from(-1), SplitDirection::Below),
+        Positions::LeftFullVer => split_config(Window::from(-1), SplitDirection::Left),
+        Positions::NewTab => {
+            nvim_oxi::api::set_current_win(window).context("failed to focus window to move")?;
+            nvim_oxi::api::command("wincmd T").context("failed to move window to new tab")?;
             return Ok(());
         }
-        Positions::SplitTop => {
-            format!("leftabove split {path}")
+        _ if window == target => {
+            if matches!(pos, Positions::Center | Positions::ReplaceKeepAlternate) {
+                return Ok(());
+            }
+            bail!("can't place a window relative to itself");
+        }
+        Positions::SplitTop => split_config(target.clone(), SplitDirection::Above),
+        Positions::SplitRight => split_config(target.clone(), SplitDirection::Right),
+        Positions::SplitBot => split_config(target.clone(), SplitDirection::Below),
+        Positions::SplitLeft => split_config(target.clone(), SplitDirection::Left),
+        Positions::CornerTopLeft
+        | Positions::CornerTopRight
+        | Positions::CornerBotLeft
+        | Positions::CornerBotRight => {
+            return in_corner(target, pos, |cfg| {
+                window
+                    .set_config(&cfg)
+                    .context("failed to move window into corner")
+            });
+        }
+        Positions::Float => float_over_config(target)?,
+        Positions::Center | Positions::ReplaceKeepAlternate => {
+            let buf = window.get_buf().context("failed to get window buffer")?;
+            show_buf_in(target, &buf, pos)?;
+            window
+                .clone()
+                .close(false)
+                .context("failed to close moved window")?;
+            *window = target.clone();
+            return Ok(());
         }
-        Positions::SplitRight => {
-            format!("belowright vertical split {path}")
+    };
+    window
+        .set_config(&cfg)
+        .context("failed to move window into split")?;
+    Ok(())
+}
+
+pub(crate) fn open_split_with(
+    source: &BufSource,
+    keep_focus_at: Option<&Window>,
+    window: &mut Window,
+    pos: Positions,
+    size: Option<SplitSize>,
+) -> Result<Window> {
+    let split_cmd = match pos {
+        Positions::TopFullHor => "topleft split",
+        Positions::LeftFullVer => "topleft vertical split",
+        Positions::BotFullHor => "botright split",
+        Positions::RightFullVer => "botright vertical split",
+        Positions::SplitTop => "leftabove split",
+        Positions::SplitRight => "belowright vertical split",
+        Positions::SplitBot => "belowright split",
+        Positions::SplitLeft => "leftabove vertical split",
+        Positions::NewTab => "tab split",
+        Positions::Center | Positions::ReplaceKeepAlternate => {
+            let buf = source.load()?;
+            show_buf_in(window, &buf, pos)?;
+            focus_after_open(keep_focus_at, window)?;
+            return Ok(window.clone());
         }
-        Positions::SplitBot => {
-            format!("belowright split {path}")
+        Positions::CornerTopLeft
+        | Positions::CornerTopRight
+        | Positions::CornerBotLeft
+        | Positions::CornerBotRight => {
+            let buf = source.load()?;
+            let new_window = in_corner(window, pos, |cfg| {
+                nvim_oxi::api::open_win(&buf, false, &cfg).context("failed to open corner window")
+            })?;
+            focus_after_open(keep_focus_at, &new_window)?;
+            return Ok(new_window);
         }
-        Positions::SplitLeft => {
-            format!("leftabove vertical split {path}")
+        Positions::Float => {
+            let buf = source.load()?;
+            let new_window = nvim_oxi::api::open_win(&buf, false, &float_over_config(window)?)
+                .context("failed to open floating window")?;
+            focus_after_open(keep_focus_at, &new_window)?;
+            return Ok(new_window);
         }
     };
-    nvim_oxi::api::command(&with_pos).context("failed to run split command")?;
+    // Measured before splitting, the split takes its space from the target
+    let space = match (size, pos.split_axis()) {
+        (Some(_), Some(axis)) => Some((axis, space_for_split(window, pos, axis)?)),
+        _ => None,
+    };
+    match source {
+        BufSource::Path(path) => {
+            nvim_oxi::api::command(&format!("{split_cmd} {path}"))
+                .context("failed to run split command")?;
+        }
+        BufSource::Buf(_) => {
+            // Not using `sbuffer`, it jumps to an existing window with 'switchbuf=useopen'
+            let buf = source.load()?;
+            nvim_oxi::api::command(split_cmd).context("failed to run split command")?;
+            nvim_oxi::api::get_current_win()
+                .set_buf(&buf)
+                .context("failed to set buffer")?;
+        }
+    }
+    // The split focuses the new window
+    let mut new_window = nvim_oxi::api::get_current_win();
+    if let (Some(size), Some((axis, available))) = (size, space) {
+        resize_split(&mut new_window, axis, available, size)?;
+    }
+    if let Some(refocus) = keep_focus_at {
+        nvim_oxi::api::set_current_win(refocus).context("failed to refocus old window")?;
+    }
+    Ok(new_window)
+}
+
+/// The size of a newly opened split, along the dimension it was split in
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum SplitSize {
+    /// Rows for horizontal splits, columns for vertical splits
+    Absolute(u32),
+    /// A fraction, between 0 and 1, of the space the split was made from
+    Ratio(f64),
+}
+
+/// Deserialized by hand in [`crate::opts`], integers and floats mean different things
+impl JsonSchema for SplitSize {
+    fn schema_name() -> Cow<'static, str> {
+        "SplitSize".into()
+    }
+
+    fn json_schema(_: &mut SchemaGenerator) -> Schema {
+        json_schema!({
+            "anyOf": [
+                { "type": "integer", "minimum": 1 },
+                { "type": "number", "exclusiveMinimum": 0, "exclusiveMaximum": 1 },
+            ]
+        })
+    }
+}
+
+impl SplitSize {
+    pub(crate) fn resolve(self, available: u32) -> u32 {
+        match self {
+            Self::Absolute(size) => size,
+            // The ratio is checked to be within (0, 1) when parsed, so this fits in `available`
+            #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+            Self::Ratio(ratio) => (f64::from(available) * ratio).round() as u32,
+        }
+        .max(1)
+    }
+}
+
+/// Which way a plain split of a window goes
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum SplitKind {
+    /// Right or below, depending on 'splitright' and 'splitbelow'
+    Default { vertical: bool },
+    /// An explicit side, from `:aboveleft`, `:belowright` and the like
+    Side(SplitDirection),
+}
+
+impl SplitKind {
+    pub(crate) fn axis(self) -> SplitAxis {
+        match self {
+            Self::Default { vertical: true }
+            | Self::Side(SplitDirection::Left | SplitDirection::Right) => SplitAxis::Cols,
+            Self::Default { vertical: false }
+            | Self::Side(SplitDirection::Above | SplitDirection::Below) => SplitAxis::Rows,
+        }
+    }
+}
+
+/// How the size of a new split is chosen
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub(crate) struct SplitSizing {
+    /// The size to use, `None` leaves the split at Vim's default of half the space
+    pub(crate) size: Option<SplitSize>,
+    /// Whether to prompt for the size after picking, falling back to `size`
+    pub(crate) prompt: bool,
+}
+
+/// The space a split at `pos` divides along `axis`, the editor for full splits, otherwise
+/// the target window
+pub(crate) fn space_for_split(target: &Window, pos: Positions, axis: SplitAxis) -> Result<u32> {
+    if pos.is_full() {
+        let editor = editor_dimensions()?;
+        return Ok(match axis {
+            SplitAxis::Cols => editor.width,
+            SplitAxis::Rows => editor.height,
+        });
+    }
+    match axis {
+        SplitAxis::Cols => target.get_width().context("failed to get target width"),
+        SplitAxis::Rows => target.get_height().context("failed to get target height"),
+    }
+}
+
+/// The size of the editor grid windows can be placed in, excluding the command line
+pub(crate) fn editor_dimensions() -> Result<WindowDimensions> {
+    let opts = OptionOpts::default();
+    let width: u32 =
+        nvim_oxi::api::get_option_value("columns", &opts).context("failed to get editor width")?;
+    let lines: u32 =
+        nvim_oxi::api::get_option_value("lines", &opts).context("failed to get editor height")?;
+    let cmdheight: u32 =
+        nvim_oxi::api::get_option_value("cmdheight", &opts).context("failed to get cmdheight")?;
+    Ok(WindowDimensions {
+        width,
+        height: lines.saturating_sub(cmdheight),
+    })
+}
+
+/// Resizes a newly opened split to `size` out of the `available` space it was made from
+pub(crate) fn resize_split(
+    window: &mut Window,
+    axis: SplitAxis,
+    available: u32,
+    size: SplitSize,
+) -> Result<()> {
+    let size = size.resolve(available);
+    match axis {
+        SplitAxis::Rows => window.set_height(size),
+        SplitAxis::Cols => window.set_width(size),
+    }
+    .context("failed to resize new split")
+}
+
+pub(crate) fn split_config(win: Window, split: SplitDirection) -> WindowConfig {
+    let mut cfg = WindowConfig::builder().split(split).build();
+    // Todo: Same as in `open_simple_split_at_win`, not exposed through the builder
+    cfg.win = Some(win);
+    cfg
+}
+
+/// Splits `target` in half for a corner position and calls `place` with the config that splits
+/// that half into the corner. The new half keeps showing the target's buffer the same way
+/// `:split` does, and is closed again if `place` fails
+fn in_corner<T>(
+    target: &Window,
+    pos: Positions,
+    place: impl FnOnce(WindowConfig) -> Result<T>,
+) -> Result<T> {
+    let (first, second) = pos.corner_splits().context("position is not a corner")?;
+    let buf = target.get_buf().context("failed to get target buffer")?;
+    let half = nvim_oxi::api::open_win(&buf, false, &split_config(target.clone(), first))
+        .context("failed to split target window")?;
+    place(split_config(half.clone(), second)).inspect_err(|_| {
+        // Already failing, the error that matters is the one from `place`
+        let _ = half.close(true);
+    })
+}
+
+/// A float covering `target`, leaving room for the border
+fn float_over_config(target: &Window) -> Result<WindowConfig> {
+    let width = target.get_width().context("failed to get target width")?;
+    let height = target.get_height().context("failed to get target height")?;
+    Ok(WindowConfig::builder()
+        .relative(WindowRelativeTo::Window(target.clone()))
+        .width(width.saturating_sub(2).max(1))
+        .height(height.saturating_sub(2).max(1))
+        .row(0.0)
+        .col(0.0)
+        .border(WindowBorder::Rounded)
+        .build())
+}
+
+/// Shows `buf` in `window`, [`Positions::ReplaceKeepAlternate`] goes through `:buffer` so that
+/// the window's previous buffer becomes its alternate file
+fn show_buf_in(window: &mut Window, buf: &Buffer, pos: Positions) -> Result<()> {
+    if matches!(pos, Positions::ReplaceKeepAlternate) {
+        nvim_oxi::api::call_function::<_, String>(
+            "win_execute",
+            (window.handle(), format!("buffer {}", buf.handle())),
+        )
+        .context("failed to switch buffer")?;
+    } else {
+        window.set_buf(buf).context("failed to set buffer")?;
+    }
+    Ok(())
+}
+
+fn focus_after_open(keep_focus_at: Option<&Window>, new_window: &Window) -> Result<()> {
     if let Some(refocus) = keep_focus_at {
         nvim_oxi::api::set_current_win(refocus).context("failed to refocus old window")?;
     } else {
-        nvim_oxi::api::set_current_win(window).context("failed to focus new window")?;
+        nvim_oxi::api::set_current_win(new_window).context("failed to focus new window")?;
     }
     Ok(())
 }
+
+/// A position in a buffer, lines and columns are 1-indexed
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Location {
+    pub(crate) lnum: usize,
+    pub(crate) col: usize,
+    pub(crate) end_lnum: Option<usize>,
+}
+
+const LOCATION_HL_GROUP: &str = "WinpickLocation";
+/// How long the lines of a location with an `end_lnum` stay highlighted
+const LOCATION_HL_MS: u32 = 1000;
+
+/// Moves the cursor of `window` to `location`, bringing it into view, and briefly highlights
+/// the location's lines if it has an `end_lnum`
+pub(crate) fn jump_to_location(window: &mut Window, location: Location) -> Result<()> {
+    let mut buf = window.get_buf().context("failed to get window buffer")?;
+    let line_count = buf
+        .line_count()
+        .context("failed to get buffer line count")?;
+    let lnum = location.lnum.min(line_count.max(1));
+    window
+        .set_cursor(lnum, location.col.saturating_sub(1))
+        .context("failed to set cursor")?;
+    nvim_oxi::api::call_function::<_, String>("win_execute", (window.handle(), "normal! zvzz"))
+        .context("failed to bring location into view")?;
+    let Some(end_lnum) = location.end_lnum else {
+        return Ok(());
+    };
+    nvim_oxi::api::command(&format!(
+        "highlight default link {LOCATION_HL_GROUP} Visual"
+    ))
+    .context("failed to set location highlight")?;
+    let ns = nvim_oxi::api::create_namespace("nvim_winpick_location");
+    buf.clear_namespace(ns, ..)
+        .context("failed to clear old location highlight")?;
+    let mark_opts = SetExtmarkOptsBuilder::default()
+        .end_row(end_lnum.min(line_count))
+        .end_col(0)
+        .hl_group(LOCATION_HL_GROUP)
+        .hl_eol(true)
+        .strict(false)
+        .build();
+    buf.set_extmark(ns, lnum - 1, 0, &mark_opts)
+        .context("failed to highlight location")?;
+    nvim_oxi::api::command(&format!(
+        "call timer_start({LOCATION_HL_MS}, {{-> execute('silent! call nvim_buf_clear_namespace({}, {ns}, 0, -1)')}})",
+        buf.handle()
+    ))
+    .context("failed to schedule location highlight removal")?;
+    Ok(())
+}
@@ -8,20 +8,51 @@ pub fn nvim_winpick() -> Dictionary {
     let pick_multiple_windows = Function::from_fn(nvim_winpick_core::pick_multiple_windows);
     let pick_focus_window = Function::from_fn(nvim_winpick_core::pick_focus_window);
     let pick_close_window = Function::from_fn(nvim_winpick_core::pick_close_window);
+    let pick_only_windows = Function::from_fn(nvim_winpick_core::pick_only_windows);
     let pick_swap_window = Function::from_fn(nvim_winpick_core::pick_swap_window);
+    let pick_stash_window = Function::from_fn(nvim_winpick_core::pick_stash_window);
+    let unstash = Function::from_fn(nvim_winpick_core::unstash);
     let open_split_window = Function::from_fn(nvim_winpick_core::open_split);
     let open_over_window = Function::from_fn(nvim_winpick_core::open_over);
     let pick_win_relative = Function::from_fn(nvim_winpick_core::pick_win_relative);
-    let entries: [(&str, Object); 9] = [
+    let pick_float_window = Function::from_fn(nvim_winpick_core::pick_float_window);
+    let pick_dock_float = Function::from_fn(nvim_winpick_core::pick_dock_float);
+    let pick_move_to_tab = Function::from_fn(nvim_winpick_core::pick_move_to_tab);
+    let pick_open_many = Function::from_fn(nvim_winpick_core::pick_open_many);
+    let place_window_relative = Function::from_fn(nvim_winpick_core::place_window_relative);
+    let winpick_undo = Function::from_fn(|()| nvim_winpick_core::winpick_undo());
+    let winpick_redo = Function::from_fn(|()| nvim_winpick_core::winpick_redo());
+    let repeat_last = Function::from_fn(nvim_winpick_core::repeat_last);
+    // Both are called with the motion type, which doesn't matter for repeating
+    let prime_repeat = Function::from_fn(|_: Object| nvim_winpick_core::prime_repeat());
+    let repeat_operator = Function::from_fn(|_: Object| nvim_winpick_core::repeat_operator());
+    let health = Function::from_fn(|()| nvim_winpick_core::health());
+    let schema = Function::from_fn(nvim_winpick_core::schema);
+    let entries: [(&str, Object); 24] = [
         ("setup", setup.into()),
         ("pick_window", pick_window.into()),
         ("pick_multiple_windows", pick_multiple_windows.into()),
         ("pick_focus_window", pick_focus_window.into()),
         ("pick_close_window", pick_close_window.into()),
+        ("pick_only_windows", pick_only_windows.into()),
         ("pick_swap_window", pick_swap_window.into()),
+        ("pick_stash_window", pick_stash_window.into()),
+        ("unstash", unstash.into()),
         ("pick_open_split", open_split_window.into()),
         ("pick_open_over", open_over_window.into()),
         ("pick_win_relative", pick_win_relative.into()),
+        ("pick_open_many", pick_open_many.into()),
+        ("place_window_relative", place_window_relative.into()),
+        ("pick_float_window", pick_float_window.into()),
+        ("pick_dock_float", pick_dock_float.into()),
+        ("pick_move_to_tab", pick_move_to_tab.into()),
+        ("winpick_undo", winpick_undo.into()),
+        ("winpick_redo", winpick_redo.into()),
+        ("repeat_last", repeat_last.into()),
+        ("prime_repeat", prime_repeat.into()),
+        ("repeat_operator", repeat_operator.into()),
+        ("health", health.into()),
+        ("schema", schema.into()),
     ];
     Dictionary::from_iter(entries)
 }
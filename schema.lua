@@ -0,0 +1,15 @@
+-- Rewrites the committed `lua/nvim_winpick/types.lua` and `nvim_winpick.schema.json` from a fresh build,
+-- run with `nvim -l schema.lua` after changing the opts
+local dir = vim.fn.fnamemodify(debug.getinfo(1, "S").source:sub(2), ":p:h")
+dofile(dir .. "/build.lua")
+vim.opt.runtimepath:prepend(dir)
+local winpick = require("nvim_winpick")
+for format, path in pairs({ lua = "lua/nvim_winpick/types.lua", json = "nvim_winpick.schema.json" }) do
+	local generated = winpick.schema(format)
+	if not generated then
+		error(string.format("failed to generate the %s schema, see the error above", format))
+	end
+	local file = assert(io.open(dir .. "/" .. path, "w"))
+	file:write(generated)
+	file:close()
+end